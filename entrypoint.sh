@@ -1,3 +1,3 @@
 #!/bin/sh
-. /app/.env
-/app/gnosispay-cli monitor
\ No newline at end of file
+# Configuration comes from the environment, e.g. a Kubernetes Secret.
+exec /app/gnosispay-cli monitor
\ No newline at end of file
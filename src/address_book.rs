@@ -0,0 +1,87 @@
+use crate::monerium::{EURE_V1_ADDRESS, EURE_V2_ADDRESS, GNOSIS_BANK};
+use alloy::primitives::{Address, address};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// Well-known contracts on Gnosis Chain, besides Monerium and Gnosis Pay.
+const KNOWN: [(Address, &str); 8] = [
+    // Bridges.
+    (
+        address!("0xf6A78083ca3e2a662D6dd1703c939c8aCE2e268d"),
+        "Omnibridge",
+    ),
+    (
+        address!("0x7301CFA0e1756B71869E93d4e4Dca5c7d0eb0AA6"),
+        "xDAI Bridge",
+    ),
+    (
+        address!("0x1231DEB6f5749EF6cE6943a275A1D3E7486F4EaE"),
+        "LI.FI",
+    ),
+    // DEX routers.
+    (
+        address!("0x9008D19f58AAbD9eD0D60971565AA8510560ab41"),
+        "CoW Protocol",
+    ),
+    (
+        address!("0xC92E8bdf79f0507f65a392b0ab4667716BFE0110"),
+        "CoW Protocol Vault Relayer",
+    ),
+    (
+        address!("0x1111111254EEB25477B68fb85Ed929f73A960582"),
+        "1inch Router v5",
+    ),
+    (
+        address!("0x111111125421cA6dc452d289314280a0f8842A65"),
+        "1inch Router v6",
+    ),
+    (
+        address!("0xDEF171Fe48CF0115B1d80b88dc8eAB59176FEe57"),
+        "ParaSwap",
+    ),
+];
+
+/// Labels of counterparties, the user's first, then well-known contracts.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AddressBook {
+    labels: BTreeMap<Address, String>,
+}
+
+impl AddressBook {
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn insert(&mut self, address: Address, label: String) {
+        self.labels.insert(address, label);
+    }
+
+    pub fn label(&self, address: Address) -> Option<&str> {
+        if let Some(label) = self.labels.get(&address) {
+            return Some(label);
+        }
+
+        if address == EURE_V2_ADDRESS {
+            Some("Monerium EURe")
+        } else if address == EURE_V1_ADDRESS {
+            Some("Monerium EURe v1")
+        } else if Address::from_str(GNOSIS_BANK).is_ok_and(|bank| bank == address) {
+            Some("Gnosis Pay settlement")
+        } else {
+            KNOWN
+                .iter()
+                .find(|(known, _)| *known == address)
+                .map(|(_, label)| *label)
+        }
+    }
+
+    /// Address with its label when known, e.g. `Omnibridge (0x…)`.
+    pub fn describe(&self, address: Address) -> String {
+        match self.label(address) {
+            Some(label) => format!("{} ({})", label, address),
+            None => address.to_string(),
+        }
+    }
+}
@@ -0,0 +1,238 @@
+use crate::prelude::*;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Smallest difference between amounts, a cent.
+const CENT: f64 = 0.005;
+
+/// Part of a transaction with its own category, the amount is positive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Split {
+    pub amount: f64,
+    pub category: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Parse `AMOUNT:CATEGORY[:NOTE]`, e.g. `12.50:Office:printer paper`.
+impl FromStr for Split {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid split `{}`, expected `AMOUNT:CATEGORY[:NOTE]`",
+                value
+            )
+        };
+        let mut parts = value.splitn(3, ':');
+        let amount: f64 = parts
+            .next()
+            .and_then(|amount| amount.trim().parse().ok())
+            .ok_or_else(invalid)?;
+        if !amount.is_finite() || amount < CENT {
+            return Err(format!("split amount of `{}` must be positive", value));
+        }
+
+        let category = parts.next().map(str::trim).unwrap_or_default();
+        if category.is_empty() {
+            return Err(invalid());
+        }
+        let note = parts
+            .next()
+            .map(str::trim)
+            .filter(|note| !note.is_empty())
+            .map(String::from);
+
+        Ok(Self {
+            amount,
+            category: category.to_string(),
+            note,
+        })
+    }
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:.2} {}", self.amount, self.category)?;
+        if let Some(note) = &self.note {
+            write!(f, " ({})", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// What the user added to a transaction.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Annotation {
+    /// Checked by the user, e.g. against the bank statement.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reviewed: bool,
+    /// Free labels, e.g. `business` or a project code.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    /// Receipt files, e.g. PDF or pictures.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub receipts: Vec<PathBuf>,
+    /// Parts adding up to the amount of the transaction.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub splits: Vec<Split>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Add a tag, unless it is already there in any case.
+    pub fn tag(&mut self, tag: &str) {
+        let tag = tag.trim();
+        if !tag.is_empty() && !self.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn untag(&mut self, tag: &str) {
+        self.tags.retain(|t| !t.eq_ignore_ascii_case(tag.trim()));
+    }
+
+    /// Set the note, an empty one removes it.
+    pub fn set_note(&mut self, note: &str) {
+        let note = note.trim();
+        self.note = (!note.is_empty()).then(|| note.to_string());
+    }
+
+    pub fn attach(&mut self, receipt: PathBuf) {
+        if !self.receipts.contains(&receipt) {
+            self.receipts.push(receipt);
+        }
+    }
+
+    pub fn detach(&mut self, receipt: &Path) {
+        self.receipts.retain(|r| r != receipt);
+    }
+
+    /// Replace the splits of a transaction of `amount`, they must add up to
+    /// it. No splits removes them.
+    pub fn split(&mut self, splits: Vec<Split>, amount: f64) -> Result<()> {
+        if splits.len() == 1 {
+            return Err("a split needs at least two parts".into());
+        }
+
+        let total: f64 = splits.iter().map(|split| split.amount).sum();
+        if !splits.is_empty() && (total - amount.abs()).abs() >= CENT {
+            return Err(format!(
+                "splits add up to {:.2} instead of {:.2}",
+                total,
+                amount.abs()
+            )
+            .into());
+        }
+
+        self.splits = splits;
+        Ok(())
+    }
+}
+
+/// Lines of `list`, nothing for an empty annotation.
+impl fmt::Display for Annotation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.tags.is_empty() {
+            writeln!(f, "{}: {}", "Tags".blue(), self.tags.join(", "))?;
+        }
+        if let Some(note) = &self.note {
+            writeln!(f, "{}: {}", "Note".blue(), note)?;
+        }
+        for receipt in &self.receipts {
+            writeln!(f, "{}: {}", "Receipt".blue(), receipt.display())?;
+        }
+        for split in &self.splits {
+            writeln!(f, "{}: {}", "Split".blue(), split)?;
+        }
+        if self.reviewed {
+            writeln!(f, "{}: {}", "Reviewed".blue(), "yes".green())?;
+        }
+        Ok(())
+    }
+}
+
+/// Annotations per transaction hash, kept in a JSON file.
+#[derive(Debug, Default)]
+pub struct Annotations {
+    path: PathBuf,
+    annotations: BTreeMap<String, Annotation>,
+}
+
+impl Annotations {
+    /// Default location, in the data directory next to the secrets.
+    pub fn default_path() -> Result<PathBuf> {
+        let Some(dir) = dirs::data_dir() else {
+            return Err("cannot find the data directory".into());
+        };
+        Ok(dir.join("gnosispay-cli").join("annotations.json"))
+    }
+
+    /// Load the annotations, none when the file does not exist yet.
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let annotations = if path.exists() {
+            let content = fs::read_to_string(&path)
+                .map_err(|err| format!("cannot read `{}`: {}", path.display(), err))?;
+            serde_json::from_str(&content)
+                .map_err(|err| format!("invalid annotations `{}`: {}", path.display(), err))?
+        } else {
+            BTreeMap::new()
+        };
+
+        Ok(Self { path, annotations })
+    }
+
+    /// Load the annotations from the default location.
+    pub fn open() -> Result<Self> {
+        Self::load(Self::default_path()?)
+    }
+
+    /// Read the file again, to pick up changes made by other commands.
+    pub fn reload(&mut self) -> Result<()> {
+        *self = Self::load(self.path.clone())?;
+        Ok(())
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&Annotation> {
+        self.annotations.get(&hash.to_lowercase())
+    }
+
+    /// Annotation of a transaction, created when missing.
+    pub fn entry(&mut self, hash: &str) -> &mut Annotation {
+        self.annotations.entry(hash.to_lowercase()).or_default()
+    }
+
+    pub fn is_reviewed(&self, hash: &str) -> bool {
+        self.get(hash).is_some_and(|annotation| annotation.reviewed)
+    }
+
+    /// Write the annotations, empty ones are dropped. The file is replaced
+    /// at once, so an interrupted save does not lose the previous ones.
+    pub fn save(&mut self) -> Result<PathBuf> {
+        self.annotations
+            .retain(|_, annotation| !annotation.is_empty());
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut temporary = self.path.clone().into_os_string();
+        temporary.push(".tmp");
+        fs::write(&temporary, serde_json::to_string_pretty(&self.annotations)?)?;
+        fs::rename(&temporary, &self.path)?;
+
+        Ok(self.path.clone())
+    }
+}
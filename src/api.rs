@@ -0,0 +1,265 @@
+use crate::filter::{Direction, Filter};
+use crate::prelude::*;
+use crate::report::{GroupBy, Report};
+use crate::transactions::{Kind, Transaction, Transactions};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use subtle::ConstantTimeEq;
+
+/// Transactions served by the API, replaced on each refresh.
+#[derive(Debug, Default)]
+pub struct Store {
+    loaded: RwLock<Option<Loaded>>,
+}
+
+#[derive(Debug)]
+struct Loaded {
+    transactions: Transactions,
+    updated_at: DateTime<Utc>,
+}
+
+impl Store {
+    /// Replace the transactions served, the whole history of the wallet.
+    pub fn update(&self, transactions: Transactions) {
+        *self.loaded.write().unwrap() = Some(Loaded {
+            transactions,
+            updated_at: Utc::now(),
+        });
+    }
+
+    /// Run `f` on the transactions, an error until they are loaded.
+    fn read<T>(
+        &self,
+        f: impl FnOnce(&Transactions, DateTime<Utc>) -> ApiResult<T>,
+    ) -> ApiResult<T> {
+        let loaded = self.loaded.read().unwrap();
+        match loaded.as_ref() {
+            Some(loaded) => f(&loaded.transactions, loaded.updated_at),
+            None => Err(ApiError(
+                StatusCode::SERVICE_UNAVAILABLE,
+                String::from("transactions are not loaded yet"),
+            )),
+        }
+    }
+}
+
+/// Error answered as JSON.
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(ErrorBody { error: self.1 })).into_response()
+    }
+}
+
+impl ApiError {
+    fn bad_request(err: impl std::fmt::Display) -> Self {
+        Self(StatusCode::BAD_REQUEST, err.to_string())
+    }
+}
+
+/// Filters as query parameters, lists are comma separated.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FilterQuery {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    min_amount: Option<f64>,
+    max_amount: Option<f64>,
+    direction: Option<String>,
+    kind: Option<String>,
+    merchant: Option<String>,
+    mcc: Option<String>,
+    category: Option<String>,
+    country: Option<String>,
+    currency: Option<String>,
+}
+
+fn split(value: &Option<String>) -> Vec<String> {
+    value
+        .iter()
+        .flat_map(|value| value.split(','))
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+impl FilterQuery {
+    fn filter(&self) -> Result<Filter> {
+        let direction = match &self.direction {
+            Some(direction) => Some(direction.parse::<Direction>()?),
+            None => None,
+        };
+        let kinds = split(&self.kind)
+            .iter()
+            .map(|kind| kind.parse::<Kind>())
+            .collect::<std::result::Result<_, _>>()?;
+        let merchant = match &self.merchant {
+            Some(pattern) => Some(Filter::merchant_pattern(pattern)?),
+            None => None,
+        };
+
+        Ok(Filter {
+            from: self.from,
+            to: self.to,
+            min_amount: self.min_amount,
+            max_amount: self.max_amount,
+            direction,
+            kinds,
+            merchant,
+            mcc: split(&self.mcc),
+            categories: split(&self.category),
+            countries: split(&self.country),
+            currencies: split(&self.currency),
+        })
+    }
+
+    /// Transactions matching the filters.
+    fn apply(&self, transactions: &Transactions) -> ApiResult<Transactions> {
+        let filter = self.filter().map_err(ApiError::bad_request)?;
+        let mut transactions = transactions.clone();
+        transactions.retain(&filter);
+        Ok(transactions)
+    }
+}
+
+#[derive(Clone)]
+struct ApiState {
+    store: Arc<Store>,
+    token: Option<Arc<str>>,
+}
+
+/// Reject requests without the bearer token, when one is set.
+async fn authorize(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    if let Some(token) = &state.token {
+        let authorization = request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok());
+
+        // Compared in constant time, not to leak the token through timing.
+        let given = authorization
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        if !bool::from(given.as_bytes().ct_eq(token.as_bytes())) {
+            return ApiError(
+                StatusCode::UNAUTHORIZED,
+                String::from("missing or invalid bearer token"),
+            )
+            .into_response();
+        }
+    }
+
+    next.run(request).await
+}
+
+async fn transactions(
+    State(state): State<ApiState>,
+    Query(query): Query<FilterQuery>,
+) -> ApiResult<Json<Vec<Transaction>>> {
+    state.store.read(|transactions, _| {
+        let mut transactions: Vec<Transaction> = query
+            .apply(transactions)?
+            .transactions
+            .into_values()
+            .collect();
+        transactions.sort_by_key(|transaction| transaction.date);
+        Ok(Json(transactions))
+    })
+}
+
+#[derive(Serialize)]
+struct Balance {
+    wallet_address: String,
+    balance: f64,
+    updated_at: DateTime<Utc>,
+}
+
+/// Balance over the whole history, the store is never filtered.
+async fn balance(State(state): State<ApiState>) -> ApiResult<Json<Balance>> {
+    state.store.read(|transactions, updated_at| {
+        Ok(Json(Balance {
+            wallet_address: transactions.wallet_address.to_string(),
+            balance: transactions.total(),
+            updated_at,
+        }))
+    })
+}
+
+async fn report(
+    State(state): State<ApiState>,
+    Path(by): Path<String>,
+    Query(query): Query<FilterQuery>,
+) -> ApiResult<Json<Report>> {
+    let group_by = match by.as_str() {
+        "monthly" => GroupBy::Month,
+        "weekly" => GroupBy::Week,
+        by => by.parse::<GroupBy>().map_err(ApiError::bad_request)?,
+    };
+
+    state
+        .store
+        .read(|transactions, _| Ok(Json(Report::new(&query.apply(transactions)?, group_by))))
+}
+
+#[derive(Serialize)]
+struct Verify {
+    count: usize,
+    total: f64,
+    updated_at: DateTime<Utc>,
+}
+
+async fn verify(
+    State(state): State<ApiState>,
+    Query(query): Query<FilterQuery>,
+) -> ApiResult<Json<Verify>> {
+    state.store.read(|transactions, updated_at| {
+        let transactions = query.apply(transactions)?;
+        Ok(Json(Verify {
+            count: transactions.transactions.len(),
+            total: transactions.total(),
+            updated_at,
+        }))
+    })
+}
+
+/// Routes of the read-only API, with an optional bearer token.
+pub fn router(store: Arc<Store>, token: Option<String>) -> Router {
+    let state = ApiState {
+        store,
+        token: token.map(Arc::from),
+    };
+
+    Router::new()
+        .route("/transactions", get(transactions))
+        .route("/balance", get(balance))
+        .route("/reports/{by}", get(report))
+        .route("/verify", get(verify))
+        .route_layer(middleware::from_fn_with_state(state.clone(), authorize))
+        .with_state(state)
+}
+
+/// Serve the read-only API on the given address, with an optional bearer token.
+pub async fn serve(store: Arc<Store>, listen: SocketAddr, token: Option<String>) -> Result<()> {
+    let app = router(store, token);
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
@@ -1,4 +1,18 @@
-use clap::{Args, Parser, Subcommand};
+use crate::config::Profile;
+use chrono::NaiveDate;
+use clap::builder::{EnumValueParser, TypedValueParser};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use gnosispay::annotations::Split;
+use gnosispay::filter::{Direction, Filter};
+use gnosispay::monerium::Token;
+use gnosispay::notify::{Priority, PushOverNotify};
+use gnosispay::prelude::*;
+use gnosispay::recording::Recording;
+use gnosispay::report::GroupBy;
+use gnosispay::summary::YearStart;
+use gnosispay::transactions::Kind;
+use std::net::SocketAddr;
+use std::path::PathBuf;
 
 #[derive(Debug, Parser)]
 #[clap(author, version, about)]
@@ -9,50 +23,716 @@ pub struct CmdArgs {
     #[arg(long, default_value_t = false)]
     pub no_color: bool,
 
+    /// Configuration file, defaults to `gnosispay-cli/config.toml` in the config directory.
+    #[arg(long, global = true, env = "GNOSISPAY_CONFIG")]
+    pub config: Option<String>,
+
+    /// Profile of the configuration file to use.
+    #[arg(long, global = true, env = "GNOSISPAY_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Save Gnosisscan and Gnosis Pay responses in this directory.
+    #[arg(long, global = true, conflicts_with = "replay")]
+    pub record: Option<PathBuf>,
+
+    /// Serve Gnosisscan and Gnosis Pay responses saved with `--record`, offline.
+    #[arg(long, global = true)]
+    pub replay: Option<PathBuf>,
+
     #[clap(subcommand)]
     pub cmd: CmdSubcommand,
 }
 
 #[derive(Debug, Clone, Args)]
 pub struct ListArgs {
+    /// Defaults to the stored `gnosisscan_api_key` secret.
     #[arg(long, env = "GNOSISSCAN_API_KEY")]
-    pub gnosisscan_api_key: String,
+    pub gnosisscan_api_key: Option<String>,
 
     #[arg(long, env = "WALLET_ADDRESS")]
-    pub wallet_address: String,
+    pub wallet_address: Option<String>,
 
+    /// Defaults to the stored `session_token` secret, then the session from `login`.
     #[arg(long, env = "SESSION_TOKEN")]
-    pub session_token: String,
+    pub session_token: Option<String>,
+
+    /// Token to follow, defaults to `eure`.
+    #[arg(long, env = "EURE_TOKEN", value_parser = TokenArg::parser())]
+    pub token: Option<Token>,
+
+    /// Used to read the spending allowance, defaults to `wss://rpc.gnosischain.com/wss`.
+    #[arg(long, env = "ETH_RPC_URL")]
+    pub rpc_url: Option<String>,
+}
+
+// Transactions narrowed down by the filters, for `list`, `verify` and
+// browsing. Not a doc comment, clap would use it as the help of the commands.
+#[derive(Debug, Clone, Args)]
+pub struct FilteredArgs {
+    #[command(flatten)]
+    pub list: ListArgs,
+
+    #[command(flatten)]
+    pub filter: FilterArgs,
+}
+
+/// Command line names of `Token`, clap stays out of the library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TokenArg {
+    /// EURe v2.
+    Eure,
+    /// EURe before the v2 migration, for older history.
+    EureV1,
+}
+
+impl From<TokenArg> for Token {
+    fn from(token: TokenArg) -> Self {
+        match token {
+            TokenArg::Eure => Token::Eure,
+            TokenArg::EureV1 => Token::EureV1,
+        }
+    }
+}
+
+impl TokenArg {
+    fn parser() -> impl TypedValueParser<Value = Token> {
+        EnumValueParser::<Self>::new().map(Token::from)
+    }
+}
+
+/// Command line names of `Priority`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PriorityArg {
+    Lowest,
+    Low,
+    Normal,
+    High,
+    Emergency,
+}
+
+impl From<PriorityArg> for Priority {
+    fn from(priority: PriorityArg) -> Self {
+        match priority {
+            PriorityArg::Lowest => Priority::Lowest,
+            PriorityArg::Low => Priority::Low,
+            PriorityArg::Normal => Priority::Normal,
+            PriorityArg::High => Priority::High,
+            PriorityArg::Emergency => Priority::Emergency,
+        }
+    }
+}
+
+impl PriorityArg {
+    fn parser() -> impl TypedValueParser<Value = Priority> {
+        EnumValueParser::<Self>::new().map(Priority::from)
+    }
+}
+
+/// Command line names of `GroupBy`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum GroupByArg {
+    Month,
+    /// ISO week.
+    Week,
+    Merchant,
+    /// Merchant category code.
+    Mcc,
+    Category,
+    Country,
+}
+
+impl From<GroupByArg> for GroupBy {
+    fn from(group_by: GroupByArg) -> Self {
+        match group_by {
+            GroupByArg::Month => GroupBy::Month,
+            GroupByArg::Week => GroupBy::Week,
+            GroupByArg::Merchant => GroupBy::Merchant,
+            GroupByArg::Mcc => GroupBy::Mcc,
+            GroupByArg::Category => GroupBy::Category,
+            GroupByArg::Country => GroupBy::Country,
+        }
+    }
+}
+
+impl GroupByArg {
+    fn parser() -> impl TypedValueParser<Value = GroupBy> {
+        EnumValueParser::<Self>::new().map(GroupBy::from)
+    }
+}
+
+/// Command line names of `Direction`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DirectionArg {
+    In,
+    Out,
+}
+
+impl From<DirectionArg> for Direction {
+    fn from(direction: DirectionArg) -> Self {
+        match direction {
+            DirectionArg::In => Direction::In,
+            DirectionArg::Out => Direction::Out,
+        }
+    }
+}
+
+/// Command line names of `Kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KindArg {
+    Mint,
+    Burn,
+    Deposit,
+    Card,
+    Sent,
+    Withdrawal,
+    Safe,
+    SafeFailure,
+    Unknown,
+}
+
+impl From<KindArg> for Kind {
+    fn from(kind: KindArg) -> Self {
+        match kind {
+            KindArg::Mint => Kind::Minted,
+            KindArg::Burn => Kind::Burned,
+            KindArg::Deposit => Kind::Deposit,
+            KindArg::Card => Kind::Card,
+            KindArg::Sent => Kind::Sent,
+            KindArg::Withdrawal => Kind::Withdrawal,
+            KindArg::Safe => Kind::SafeTransaction,
+            KindArg::SafeFailure => Kind::SafeFailure,
+            KindArg::Unknown => Kind::Unknown,
+        }
+    }
+}
+
+// Conditions on the transactions listed, exported, verified or reported, not a
+// doc comment as clap would use it as the help of the commands.
+#[derive(Debug, Clone, Default, Args)]
+#[command(next_help_heading = "Filters")]
+pub struct FilterArgs {
+    /// First day, e.g. `2025-01-01`.
+    #[arg(long)]
+    pub from: Option<NaiveDate>,
+
+    /// Last day, included.
+    #[arg(long)]
+    pub to: Option<NaiveDate>,
+
+    /// Minimum amount, either direction.
+    #[arg(long)]
+    pub min_amount: Option<f64>,
+
+    /// Maximum amount, either direction.
+    #[arg(long)]
+    pub max_amount: Option<f64>,
+
+    #[arg(long, value_enum)]
+    pub direction: Option<DirectionArg>,
+
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub kind: Vec<KindArg>,
+
+    /// Substring or regular expression of the merchant name, case insensitive.
+    #[arg(long)]
+    pub merchant: Option<String>,
+
+    /// Merchant category code, e.g. `5411,5499`.
+    #[arg(long, value_delimiter = ',')]
+    pub mcc: Vec<String>,
+
+    /// Category, e.g. `Groceries`.
+    #[arg(long, value_delimiter = ',')]
+    pub category: Vec<String>,
+
+    /// Merchant country name, e.g. `France`.
+    #[arg(long, value_delimiter = ',')]
+    pub country: Vec<String>,
+
+    /// Currency paid in, as numeric ISO 4217 code, symbol or name, e.g. `840`, `$` or `US Dollar`.
+    #[arg(long, value_delimiter = ',')]
+    pub currency: Vec<String>,
+}
+
+impl FilterArgs {
+    pub fn filter(&self) -> Result<Filter> {
+        let merchant = match &self.merchant {
+            Some(pattern) => Some(Filter::merchant_pattern(pattern)?),
+            None => None,
+        };
+
+        Ok(Filter {
+            from: self.from,
+            to: self.to,
+            min_amount: self.min_amount,
+            max_amount: self.max_amount,
+            direction: self.direction.map(Direction::from),
+            kinds: self.kind.iter().copied().map(Kind::from).collect(),
+            merchant,
+            mcc: self.mcc.clone(),
+            categories: self.category.clone(),
+            countries: self.country.clone(),
+            currencies: self.currency.clone(),
+        })
+    }
+}
+
+impl ListArgs {
+    /// Fill unset arguments from the profile, flags and environment win.
+    pub fn merge(&mut self, profile: &Profile) {
+        merge(&mut self.rpc_url, &profile.rpc_url);
+        merge(&mut self.gnosisscan_api_key, &profile.gnosisscan_api_key);
+        merge(&mut self.wallet_address, &profile.wallet_address);
+        merge(&mut self.session_token, &profile.session_token);
+        merge(&mut self.token, &profile.token);
+    }
+
+    pub fn wallet_address(&self) -> Result<String> {
+        match &self.wallet_address {
+            Some(wallet_address) => Ok(wallet_address.clone()),
+            None => Err("a wallet address is required, set WALLET_ADDRESS or use a profile".into()),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Args)]
 pub struct OnchainArgs {
     #[arg(long, env = "WALLET_ADDRESS")]
-    pub wallet_address: String,
-
-    #[arg(
-        long,
-        env = "ETH_RPC_URL",
-        default_value_t = String::from("wss://rpc.gnosischain.com/wss")
-    )]
-    pub rpc_url: String,
+    pub wallet_address: Option<String>,
+
+    /// TOML file with the wallets to monitor and their recipients.
+    #[arg(long, env = "MONITOR_WALLETS")]
+    pub wallets: Option<String>,
+
+    /// Defaults to `wss://rpc.gnosischain.com/wss`.
+    #[arg(long, env = "ETH_RPC_URL")]
+    pub rpc_url: Option<String>,
+
+    /// Token to follow, defaults to `eure`.
+    #[arg(long, env = "EURE_TOKEN", value_parser = TokenArg::parser())]
+    pub token: Option<Token>,
+
+    /// Defaults to the stored `pushover_user` secret.
     #[arg(long, env = "PUSHOVER_USER")]
-    pub pushover_user: String,
+    pub pushover_user: Option<String>,
+    /// Defaults to the stored `pushover_token` secret.
     #[arg(long, env = "PUSHOVER_TOKEN")]
-    pub pushover_token: String,
+    pub pushover_token: Option<String>,
+    #[arg(long, env = "PUSHOVER_DEVICE")]
+    pub pushover_device: Option<String>,
+    #[arg(long, env = "PUSHOVER_SOUND")]
+    pub pushover_sound: Option<String>,
+    /// Defaults to `normal`.
+    #[arg(long, value_parser = PriorityArg::parser())]
+    pub pushover_priority: Option<Priority>,
+    /// Seconds between retries of emergency notifications.
+    #[arg(long, default_value_t = PushOverNotify::EMERGENCY_RETRY)]
+    pub pushover_retry: u32,
+    /// Seconds before emergency notifications stop retrying.
+    #[arg(long, default_value_t = PushOverNotify::EMERGENCY_EXPIRE)]
+    pub pushover_expire: u32,
+    /// TOML file with alerting rules.
+    #[arg(long, env = "MONITOR_RULES")]
+    pub rules: Option<String>,
+    /// Used to enrich notifications with merchant details.
+    #[arg(long, env = "SESSION_TOKEN")]
+    pub session_token: Option<String>,
+    /// Address to serve `/healthz` and `/metrics` on, e.g. `0.0.0.0:9100`.
+    #[arg(long, env = "METRICS_LISTEN")]
+    pub metrics_listen: Option<SocketAddr>,
+    /// Seconds without a new block before `/healthz` fails.
+    #[arg(long, default_value_t = 60)]
+    pub health_max_block_age: i64,
+    /// Notify when the EURe balance falls below this amount.
+    #[arg(long, env = "MIN_EURE_BALANCE")]
+    pub min_eure_balance: Option<f64>,
+    /// Notify when the xDAI balance, used for gas, falls below this amount.
+    #[arg(long, env = "MIN_XDAI_BALANCE")]
+    pub min_xdai_balance: Option<f64>,
+    /// Seconds between balance checks.
+    #[arg(long, default_value_t = 3600, value_parser = clap::value_parser!(u64).range(1..))]
+    pub balance_interval: u64,
+    /// Notify when a subscription charges another amount than usual, needs a session token.
+    #[arg(long, env = "MONITOR_SUBSCRIPTION_ALERTS")]
+    pub subscription_alerts: bool,
+    /// Publish transfers and balances for Home Assistant, e.g. `mqtt://localhost:1883`.
+    #[arg(long, env = "MQTT_URL")]
+    pub mqtt_url: Option<String>,
+    #[arg(long, env = "MQTT_USERNAME")]
+    pub mqtt_username: Option<String>,
+    /// Defaults to the stored `mqtt_password` secret.
+    #[arg(long, env = "MQTT_PASSWORD")]
+    pub mqtt_password: Option<String>,
+    /// Defaults to `gnosispay`.
+    #[arg(long, env = "MQTT_TOPIC_PREFIX")]
+    pub mqtt_topic_prefix: Option<String>,
+    /// Defaults to `homeassistant`.
+    #[arg(long, env = "MQTT_DISCOVERY_PREFIX")]
+    pub mqtt_discovery_prefix: Option<String>,
+}
+
+impl OnchainArgs {
+    /// Default websocket endpoint.
+    pub const RPC_URL: &str = "wss://rpc.gnosischain.com/wss";
+
+    /// Fill unset arguments from the profile, flags and environment win.
+    pub fn merge(&mut self, profile: &Profile) -> Result<()> {
+        merge(&mut self.wallet_address, &profile.wallet_address);
+        merge(&mut self.wallets, &profile.monitor.wallets);
+        merge(&mut self.rpc_url, &profile.rpc_url);
+        merge(&mut self.token, &profile.token);
+        merge(&mut self.pushover_user, &profile.notify.pushover_user);
+        merge(&mut self.pushover_token, &profile.notify.pushover_token);
+        merge(&mut self.pushover_device, &profile.notify.pushover_device);
+        merge(&mut self.pushover_sound, &profile.notify.pushover_sound);
+        merge(
+            &mut self.pushover_priority,
+            &profile.notify.pushover_priority,
+        );
+        merge(&mut self.rules, &profile.monitor.rules);
+        merge(&mut self.session_token, &profile.session_token);
+        merge(
+            &mut self.min_eure_balance,
+            &profile.monitor.min_eure_balance,
+        );
+        merge(
+            &mut self.min_xdai_balance,
+            &profile.monitor.min_xdai_balance,
+        );
+
+        merge(&mut self.mqtt_url, &profile.monitor.mqtt_url);
+        merge(&mut self.mqtt_username, &profile.monitor.mqtt_username);
+        merge(&mut self.mqtt_password, &profile.monitor.mqtt_password);
+        merge(
+            &mut self.mqtt_topic_prefix,
+            &profile.monitor.mqtt_topic_prefix,
+        );
+        merge(
+            &mut self.mqtt_discovery_prefix,
+            &profile.monitor.mqtt_discovery_prefix,
+        );
+
+        self.subscription_alerts |= profile.monitor.subscription_alerts.unwrap_or_default();
+
+        if self.metrics_listen.is_none()
+            && let Some(listen) = &profile.monitor.metrics_listen
+        {
+            self.metrics_listen = Some(listen.parse()?);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct LoginArgs {
+    /// Private key of the Safe owner.
+    #[arg(long, env = "PRIVATE_KEY", conflicts_with = "keystore")]
+    pub private_key: Option<String>,
+
+    /// Encrypted JSON keystore of the Safe owner.
+    #[arg(long, env = "KEYSTORE", required_unless_present = "private_key")]
+    pub keystore: Option<String>,
+
+    /// Prompted for when not set.
+    #[arg(long, env = "KEYSTORE_PASSWORD")]
+    pub keystore_password: Option<String>,
+
+    #[arg(long, default_value_t = String::from("https://api.gnosispay.com"))]
+    pub api_url: String,
+
+    /// Domain of the Sign-In With Ethereum message.
+    #[arg(long, default_value_t = String::from("app.gnosispay.com"))]
+    pub domain: String,
+
+    /// URI of the Sign-In With Ethereum message.
+    #[arg(long, default_value_t = String::from("https://app.gnosispay.com"))]
+    pub uri: String,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SecretsArgs {
+    #[clap(subcommand)]
+    pub cmd: SecretsSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum SecretsSubcommand {
+    /// Store a credential, the value is prompted for.
+    Set {
+        /// e.g. session_token, gnosisscan_api_key, pushover_user, pushover_token.
+        name: String,
+    },
+    /// Remove a stored credential.
+    Remove { name: String },
+    /// List stored credentials, without their values.
+    List,
+}
+
+impl CmdArgs {
+    pub fn recording(&self) -> Option<Recording> {
+        match (&self.record, &self.replay) {
+            (Some(dir), _) => Some(Recording::Record(dir.clone())),
+            (None, Some(dir)) => Some(Recording::Replay(dir.clone())),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct LimitsArgs {
+    #[arg(long, env = "WALLET_ADDRESS")]
+    pub wallet_address: Option<String>,
+
+    /// Defaults to `wss://rpc.gnosischain.com/wss`.
+    #[arg(long, env = "ETH_RPC_URL")]
+    pub rpc_url: Option<String>,
+
+    /// Token of the allowance, defaults to `eure`.
+    #[arg(long, env = "EURE_TOKEN", value_parser = TokenArg::parser())]
+    pub token: Option<Token>,
+}
+
+impl LimitsArgs {
+    /// Fill unset arguments from the profile, flags and environment win.
+    pub fn merge(&mut self, profile: &Profile) {
+        merge(&mut self.wallet_address, &profile.wallet_address);
+        merge(&mut self.rpc_url, &profile.rpc_url);
+        merge(&mut self.token, &profile.token);
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SafeArgs {
+    #[arg(long, env = "WALLET_ADDRESS", global = true)]
+    pub wallet_address: Option<String>,
+
+    /// Defaults to `wss://rpc.gnosischain.com/wss`.
+    #[arg(long, env = "ETH_RPC_URL", global = true)]
+    pub rpc_url: Option<String>,
+
+    #[clap(subcommand)]
+    pub cmd: SafeSubcommand,
+}
+
+impl SafeArgs {
+    /// Fill unset arguments from the profile, flags and environment win.
+    pub fn merge(&mut self, profile: &Profile) {
+        merge(&mut self.wallet_address, &profile.wallet_address);
+        merge(&mut self.rpc_url, &profile.rpc_url);
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum SafeSubcommand {
+    /// Show owners, threshold, modules and nonce.
+    Info,
+    /// List transactions queued in the Delay module.
+    Queue,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ConfigArgs {
+    #[clap(subcommand)]
+    pub cmd: ConfigSubcommand,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum ConfigSubcommand {
+    /// Show the selected profile, secrets are redacted.
+    Show {
+        /// Show all profiles.
+        #[arg(long, default_value_t = false)]
+        all: bool,
+    },
+    /// Print the configuration file location.
+    Path,
 }
 
 #[derive(Debug, Clone, Args)]
 pub struct ExportArgs {
     #[command(flatten)]
     pub list: ListArgs,
-    pub filename: String,
+
+    #[command(flatten)]
+    pub filter: FilterArgs,
+
+    /// Defaults to `export.filename` of the profile.
+    pub filename: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+    pub format: ExportFormat,
+}
+
+/// Output of the `export` command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Qif,
+    Ofx,
+    /// ledger journal, also read by hledger.
+    Ledger,
+}
+
+/// Output of the `report` command.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    #[default]
+    Table,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ReportArgs {
+    #[command(flatten)]
+    pub list: ListArgs,
+
+    #[command(flatten)]
+    pub filter: FilterArgs,
+
+    #[arg(long, default_value = "month", value_parser = GroupByArg::parser())]
+    pub by: GroupBy,
+
+    #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+    pub format: ReportFormat,
+
+    /// Write the CSV or JSON report to this file instead of the terminal.
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SummaryArgs {
+    #[command(flatten)]
+    pub list: ListArgs,
+
+    /// Fiscal year, defaults to the current one.
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1970..=9999))]
+    pub year: Option<i32>,
+
+    /// First day of the fiscal year as `MM-DD`, defaults to `01-01`.
+    #[arg(long, env = "FISCAL_YEAR_START")]
+    pub fiscal_year_start: Option<YearStart>,
+
+    #[arg(long, value_enum, default_value_t = ReportFormat::Table)]
+    pub format: ReportFormat,
+
+    /// Write the CSV or JSON summary to this file instead of the terminal.
+    #[arg(long)]
+    pub output: Option<String>,
+}
+
+impl SummaryArgs {
+    /// Fill unset arguments from the profile, flags and environment win.
+    pub fn merge(&mut self, profile: &Profile) {
+        self.list.merge(profile);
+        merge(&mut self.fiscal_year_start, &profile.fiscal_year_start);
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct AnnotateArgs {
+    /// Hash of the transaction.
+    pub hash: String,
+
+    /// Add a tag, e.g. `business` or a project code.
+    #[arg(long)]
+    pub tag: Vec<String>,
+
+    /// Remove a tag.
+    #[arg(long)]
+    pub untag: Vec<String>,
+
+    /// Set the note, an empty one removes it.
+    #[arg(long)]
+    pub note: Option<String>,
+
+    /// Attach a receipt file, e.g. a PDF or a picture.
+    #[arg(long)]
+    pub receipt: Vec<PathBuf>,
+
+    /// Detach a receipt file.
+    #[arg(long)]
+    pub remove_receipt: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct SplitArgs {
+    #[command(flatten)]
+    pub list: ListArgs,
+
+    /// Hash of the transaction.
+    pub hash: String,
+
+    /// Part of the amount as `AMOUNT:CATEGORY[:NOTE]`, at least two adding
+    /// up to the amount of the transaction.
+    #[arg(long = "part", required_unless_present = "clear")]
+    pub parts: Vec<Split>,
+
+    /// Remove the splits.
+    #[arg(long, conflicts_with = "parts")]
+    pub clear: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct ServeArgs {
+    #[command(flatten)]
+    pub list: ListArgs,
+
+    /// Address to serve the API on.
+    #[arg(long, env = "API_LISTEN", default_value = "127.0.0.1:8080")]
+    pub listen: SocketAddr,
+
+    /// Require `Authorization: Bearer <token>` on every request.
+    #[arg(long, env = "API_TOKEN")]
+    pub api_token: Option<String>,
+
+    /// Seconds between reloads of the transactions.
+    #[arg(long, default_value_t = 900, value_parser = clap::value_parser!(u64).range(1..))]
+    pub refresh_interval: u64,
+}
+
+/// Use the profile value when the argument was not given.
+fn merge<T: Clone>(value: &mut Option<T>, profile: &Option<T>) {
+    if value.is_none() {
+        *value = profile.clone();
+    }
 }
 
 #[derive(Debug, Subcommand)]
 pub enum CmdSubcommand {
-    List(ListArgs),
+    List(FilteredArgs),
     Export(ExportArgs),
-    Verify(ListArgs),
-    Monitor(OnchainArgs),
+    Verify(FilteredArgs),
+    Monitor(Box<OnchainArgs>),
+    /// Sign in with the Safe owner key and store the session.
+    Login(LoginArgs),
+    /// Manage credentials stored in an encrypted file.
+    Secrets(SecretsArgs),
+    /// Inspect the configuration file.
+    Config(ConfigArgs),
+    /// Inspect the Safe behind the wallet.
+    Safe(SafeArgs),
+    /// Show the card spending allowance.
+    Limits(LimitsArgs),
+    /// Aggregate spending by month, week, merchant, category or country.
+    Report(ReportArgs),
+    /// Totals of a fiscal year for tax filing: card spend, sends, top-ups, refunds.
+    Summary(SummaryArgs),
+    /// Show spending against the budgets of the profile.
+    Budget(ListArgs),
+    /// Detect subscriptions and recurring card payments.
+    Subscriptions(ListArgs),
+    /// Browse transactions and mark them as reviewed.
+    Tui(FilteredArgs),
+    /// Tag, note or attach receipts to a transaction.
+    Annotate(AnnotateArgs),
+    /// Split a transaction across categories.
+    Split(SplitArgs),
+    /// List card payments without a receipt, e.g. above `--min-amount`.
+    MissingReceipts(FilteredArgs),
+    /// Serve transactions, balance and reports over a read-only HTTP API.
+    Serve(ServeArgs),
 }
@@ -0,0 +1,170 @@
+use crate::prelude::*;
+use crate::secrets::{self, Secrets};
+use alloy::hex;
+use alloy::primitives::Address;
+use alloy::signers::Signer;
+use alloy::signers::local::PrivateKeySigner;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Gnosis chain ID, used in the Sign-In With Ethereum message.
+const GNOSIS_CHAIN_ID: u64 = 100;
+
+/// A Gnosis Pay session, either copied from the browser or obtained with `login`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "token", rename_all = "lowercase")]
+pub enum Auth {
+    /// The `__Secure-authjs.session-token` cookie from app.gnosispay.com.
+    Cookie(String),
+    /// A JWT from the Gnosis Pay API, obtained with Sign-In With Ethereum.
+    Bearer(String),
+}
+
+/// Session obtained by the `login` command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Session {
+    pub address: Address,
+    pub created_at: DateTime<Utc>,
+    pub auth: Auth,
+}
+
+impl Auth {
+    /// Expiry of the token, when it can be decoded. The browser cookie is
+    /// encrypted, only JWTs from `login` carry a readable expiry.
+    pub fn expires_at(&self) -> Option<DateTime<Utc>> {
+        let Auth::Bearer(token) = self else {
+            return None;
+        };
+
+        #[derive(Deserialize)]
+        struct Claims {
+            exp: i64,
+        }
+
+        let payload = token.split('.').nth(1)?;
+        let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+        let claims: Claims = serde_json::from_slice(&payload).ok()?;
+        DateTime::from_timestamp(claims.exp, 0)
+    }
+}
+
+impl Session {
+    /// Load the session stored in the encrypted secrets.
+    pub fn load() -> Result<Self> {
+        match Secrets::lookup(secrets::SESSION)? {
+            Some(session) => Ok(serde_json::from_str(&session)?),
+            None => Err("no session stored, run `login` first or set SESSION_TOKEN".into()),
+        }
+    }
+
+    /// Store the session in the encrypted secrets.
+    pub fn save(&self) -> Result<PathBuf> {
+        let mut secrets = Secrets::open()?;
+        secrets.set(secrets::SESSION, serde_json::to_string(self)?);
+        secrets.save()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChallengeRequest {
+    message: String,
+    signature: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChallengeResponse {
+    token: String,
+}
+
+/// Sign-In With Ethereum on the Gnosis Pay API.
+pub struct SiweLogin {
+    api_url: String,
+    domain: String,
+    uri: String,
+    client: reqwest::Client,
+}
+
+impl SiweLogin {
+    pub fn new(api_url: String, domain: String, uri: String) -> Self {
+        Self {
+            api_url,
+            domain,
+            uri,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Share a client, and its connection pool, with other services.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Build the EIP-4361 message to sign.
+    pub fn message(&self, address: Address, nonce: &str, issued_at: DateTime<Utc>) -> String {
+        format!(
+            "{domain} wants you to sign in with your Ethereum account:\n\
+             {address}\n\
+             \n\
+             Sign in with Ethereum to the app.\n\
+             \n\
+             URI: {uri}\n\
+             Version: 1\n\
+             Chain ID: {chain_id}\n\
+             Nonce: {nonce}\n\
+             Issued At: {issued_at}",
+            domain = self.domain,
+            address = address.to_checksum(None),
+            uri = self.uri,
+            chain_id = GNOSIS_CHAIN_ID,
+            nonce = nonce,
+            issued_at = issued_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+        )
+    }
+
+    /// Fetch a nonce, sign the message and exchange it for a session.
+    pub async fn login(&self, signer: &PrivateKeySigner) -> Result<Session> {
+        // Fetch a nonce.
+        let url = format!("{}/api/v1/auth/nonce", self.api_url);
+        let response = self.client.get(&url).send().await?;
+        if !response.status().is_success() {
+            return Err(format!("Cannot get nonce: {}", response.status()).into());
+        }
+        let nonce = response.text().await?.trim().to_string();
+
+        // Sign the message.
+        let address = signer.address();
+        let created_at = Utc::now();
+        let message = self.message(address, &nonce, created_at);
+        let signature = signer.sign_message(message.as_bytes()).await?;
+
+        // Exchange it for a JWT.
+        let url = format!("{}/api/v1/auth/challenge", self.api_url);
+        let response = self
+            .client
+            .post(&url)
+            .json(&ChallengeRequest {
+                message,
+                signature: hex::encode_prefixed(signature.as_bytes()),
+            })
+            .send()
+            .await?;
+
+        let challenge = match response.status() {
+            reqwest::StatusCode::OK => response.json::<ChallengeResponse>().await?,
+            status => {
+                let body = response.text().await.unwrap_or_default();
+                return Err(format!("Sign in failed ({}): {}", status, body).into());
+            }
+        };
+
+        Ok(Session {
+            address,
+            created_at,
+            auth: Auth::Bearer(challenge.token),
+        })
+    }
+}
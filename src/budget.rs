@@ -0,0 +1,200 @@
+use crate::prelude::*;
+use crate::transactions::{Kind, Part, Transaction};
+use chrono::{DateTime, Datelike, Days, NaiveDate, TimeZone};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Share of a budget spent before warning.
+pub const WARNING: f64 = 0.8;
+
+/// Width of the progress bar.
+const BAR_WIDTH: usize = 20;
+
+/// Period over which a budget is spent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Period {
+    #[default]
+    Monthly,
+    /// ISO week, starting on Monday.
+    Weekly,
+}
+
+impl Period {
+    /// First day of the period containing `date`.
+    pub fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Period::Monthly => date - Days::new(date.day0() as u64),
+            Period::Weekly => date - Days::new(date.weekday().num_days_from_monday() as u64),
+        }
+    }
+}
+
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Period::Monthly => write!(f, "month"),
+            Period::Weekly => write!(f, "week"),
+        }
+    }
+}
+
+/// Part of a payment in the category, case insensitive.
+fn in_category(part: &Part, category: &str) -> bool {
+    part.category
+        .is_some_and(|c| c.eq_ignore_ascii_case(category.trim()))
+}
+
+/// Amount allowed on card payments of a category or a merchant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Budget {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Merchant name, case insensitive.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merchant: Option<String>,
+    pub amount: f64,
+    #[serde(default)]
+    pub period: Period,
+}
+
+impl Budget {
+    /// A budget follows either a category or a merchant.
+    pub fn validate(&self) -> Result<()> {
+        if self.category.is_some() == self.merchant.is_some() {
+            return Err("a budget needs either a `category` or a `merchant`".into());
+        }
+        if self.amount <= 0.0 {
+            return Err(format!("budget `{}` must have a positive amount", self.name()).into());
+        }
+        Ok(())
+    }
+
+    /// Category or merchant followed.
+    pub fn name(&self) -> &str {
+        self.category
+            .as_deref()
+            .or(self.merchant.as_deref())
+            .unwrap_or_default()
+    }
+
+    /// Card payments counted in the budget.
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        if transaction.kind != Kind::Card {
+            return false;
+        }
+
+        match (&self.category, &self.merchant) {
+            (Some(category), _) => transaction
+                .parts()
+                .iter()
+                .any(|part| in_category(part, category)),
+            (None, Some(merchant)) => transaction
+                .name
+                .trim()
+                .eq_ignore_ascii_case(merchant.trim()),
+            (None, None) => false,
+        }
+    }
+
+    /// Amount of a card payment counted in the budget, negative for refunds.
+    /// Only the parts of a split payment in the category are counted.
+    pub fn spent_on(&self, transaction: &Transaction) -> f64 {
+        if !self.matches(transaction) {
+            return 0.0;
+        }
+
+        match &self.category {
+            Some(category) => -transaction
+                .parts()
+                .iter()
+                .filter(|part| in_category(part, category))
+                .map(|part| part.amount)
+                .sum::<f64>(),
+            None => -transaction.amount,
+        }
+    }
+
+    /// Amount spent in the period containing `now`, refunds are deducted.
+    pub fn spent<'a, Tz: TimeZone>(
+        &self,
+        transactions: impl IntoIterator<Item = &'a Transaction>,
+        now: &DateTime<Tz>,
+    ) -> f64 {
+        let today = now.date_naive();
+        let start = self.period.start(today);
+
+        transactions
+            .into_iter()
+            .filter(|transaction| {
+                let date = transaction.date.with_timezone(&now.timezone()).date_naive();
+                start <= date && date <= today
+            })
+            .map(|transaction| self.spent_on(transaction))
+            .sum()
+    }
+
+    /// Threshold crossed by spending from `before` to `after`, the highest
+    /// one when both are.
+    pub fn crossed(&self, before: f64, after: f64) -> Option<f64> {
+        [1.0, WARNING]
+            .into_iter()
+            .find(|threshold| before < threshold * self.amount && after >= threshold * self.amount)
+    }
+}
+
+/// Spending of a budget in the current period.
+#[derive(Debug, Clone)]
+pub struct Progress {
+    pub budget: Budget,
+    pub spent: f64,
+}
+
+impl Progress {
+    /// Progress of each budget in the period containing `now`.
+    pub fn of<'a, Tz: TimeZone>(
+        budgets: &[Budget],
+        transactions: impl IntoIterator<Item = &'a Transaction> + Clone,
+        now: &DateTime<Tz>,
+    ) -> Vec<Self> {
+        budgets
+            .iter()
+            .map(|budget| Self {
+                budget: budget.clone(),
+                spent: budget.spent(transactions.clone(), now),
+            })
+            .collect()
+    }
+
+    /// Share of the budget spent.
+    pub fn ratio(&self) -> f64 {
+        self.spent / self.budget.amount
+    }
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let ratio = self.ratio().max(0.0);
+        let filled = ((ratio * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+        let bar = format!("{}{}", "#".repeat(filled), "-".repeat(BAR_WIDTH - filled));
+        let bar = if ratio >= 1.0 {
+            bar.red()
+        } else if ratio >= WARNING {
+            bar.yellow()
+        } else {
+            bar.green()
+        };
+
+        write!(
+            f,
+            "[{}] {:>4.0}%  {:.2} / {:.2} per {}",
+            bar,
+            ratio * 100.0,
+            self.spent,
+            self.budget.amount,
+            self.budget.period
+        )
+    }
+}
@@ -0,0 +1,168 @@
+use gnosispay::address_book::AddressBook;
+use gnosispay::budget::Budget;
+use gnosispay::mcc::Overrides;
+use gnosispay::monerium::Token;
+use gnosispay::notify::Priority;
+use gnosispay::prelude::*;
+use gnosispay::summary::YearStart;
+use gnosispay::transactions::CsvOptions;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Replaces secrets in `config show`.
+const REDACTED: &str = "********";
+
+/// CSV export settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportConfig {
+    /// Default file for `export`.
+    pub filename: Option<String>,
+    pub delimiter: Option<char>,
+    /// chrono format, e.g. `%Y-%m-%d`.
+    pub date_format: Option<String>,
+}
+
+/// Pushover settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyConfig {
+    pub pushover_user: Option<String>,
+    pub pushover_token: Option<String>,
+    pub pushover_device: Option<String>,
+    pub pushover_sound: Option<String>,
+    pub pushover_priority: Option<Priority>,
+}
+
+/// Monitor settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorConfig {
+    /// TOML file with the wallets to monitor.
+    pub wallets: Option<String>,
+    /// TOML file with alerting rules.
+    pub rules: Option<String>,
+    pub metrics_listen: Option<String>,
+    pub min_eure_balance: Option<f64>,
+    pub min_xdai_balance: Option<f64>,
+    /// Warn when a subscription charges another amount than usual.
+    pub subscription_alerts: Option<bool>,
+    /// Broker for Home Assistant, e.g. `mqtt://localhost:1883`.
+    pub mqtt_url: Option<String>,
+    pub mqtt_username: Option<String>,
+    pub mqtt_password: Option<String>,
+    pub mqtt_topic_prefix: Option<String>,
+    pub mqtt_discovery_prefix: Option<String>,
+}
+
+/// Settings for a wallet or a person.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub wallet_address: Option<String>,
+    pub token: Option<Token>,
+    pub rpc_url: Option<String>,
+    pub gnosisscan_api_key: Option<String>,
+    pub session_token: Option<String>,
+    /// First day of the fiscal year for `summary`, as `MM-DD`.
+    pub fiscal_year_start: Option<YearStart>,
+    #[serde(default)]
+    pub export: ExportConfig,
+    #[serde(default)]
+    pub notify: NotifyConfig,
+    #[serde(default)]
+    pub monitor: MonitorConfig,
+    /// Categories per merchant or MCC, replacing the built-in table.
+    #[serde(default)]
+    pub categories: Overrides,
+    /// Spending limits per category or merchant.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub budgets: Vec<Budget>,
+    /// Labels of counterparties, by address.
+    #[serde(default, skip_serializing_if = "AddressBook::is_empty")]
+    pub addresses: AddressBook,
+}
+
+impl Profile {
+    /// Copy of the profile with secrets hidden.
+    pub fn redacted(&self) -> Self {
+        let redact = |value: &Option<String>| value.as_ref().map(|_| String::from(REDACTED));
+
+        let mut profile = self.clone();
+        profile.gnosisscan_api_key = redact(&self.gnosisscan_api_key);
+        profile.session_token = redact(&self.session_token);
+        profile.notify.pushover_user = redact(&self.notify.pushover_user);
+        profile.notify.pushover_token = redact(&self.notify.pushover_token);
+        profile.monitor.mqtt_password = redact(&self.monitor.mqtt_password);
+        profile
+    }
+}
+
+/// Configuration file with named profiles.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Profile used when `--profile` is not given.
+    pub default_profile: Option<String>,
+    #[serde(default, rename = "profile")]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl Config {
+    /// Default location, `$XDG_CONFIG_HOME/gnosispay-cli/config.toml` on Linux.
+    pub fn default_path() -> Result<PathBuf> {
+        let Some(dir) = dirs::config_dir() else {
+            return Err("cannot find the config directory".into());
+        };
+        Ok(dir.join("gnosispay-cli").join("config.toml"))
+    }
+
+    /// Load the configuration. A missing file is only an error when it was
+    /// explicitly asked for.
+    pub fn load(path: Option<&str>) -> Result<(PathBuf, Self)> {
+        let (path, explicit) = match path {
+            Some(path) => (PathBuf::from(path), true),
+            None => (Self::default_path()?, false),
+        };
+
+        if !explicit && !path.exists() {
+            return Ok((path, Self::default()));
+        }
+
+        let content = fs::read_to_string(&path)
+            .map_err(|err| format!("cannot read config `{}`: {}", path.display(), err))?;
+        let config: Self = toml::from_str(&content)
+            .map_err(|err| format!("invalid config `{}`: {}", path.display(), err))?;
+
+        for (name, profile) in &config.profiles {
+            if let Some(format) = &profile.export.date_format {
+                CsvOptions::check_date_format(format).map_err(|err| {
+                    format!(
+                        "invalid config `{}`, profile `{}`: {}",
+                        path.display(),
+                        name,
+                        err
+                    )
+                })?;
+            }
+        }
+
+        Ok((path, config))
+    }
+
+    /// Select a profile by name, or the default one. No profile at all gives
+    /// an empty profile so that only flags and environment are used.
+    pub fn profile(&self, name: Option<&str>) -> Result<(Option<String>, Profile)> {
+        let name = name.or(self.default_profile.as_deref());
+
+        match name {
+            Some(name) => match self.profiles.get(name) {
+                Some(profile) => Ok((Some(name.to_string()), profile.clone())),
+                None => Err(format!("unknown profile `{}`", name).into()),
+            },
+            None => Ok((None, Profile::default())),
+        }
+    }
+}
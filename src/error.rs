@@ -1,4 +1,5 @@
 // #[cfg(debug_assertions)]
-pub type Error = Box<dyn std::error::Error>;
+/// Error returned by the library, `Send` so clients can be used from spawned tasks.
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
 
 // TODO: Implement customs errors.
@@ -0,0 +1,120 @@
+use crate::prelude::*;
+use crate::transactions::{Kind, Transaction};
+use chrono::{Local, NaiveDate};
+use regex::{Regex, RegexBuilder};
+use std::str::FromStr;
+
+/// Money received or spent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    In,
+    Out,
+}
+
+/// Parse `in` or `out`, case insensitive.
+impl FromStr for Direction {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "in" => Ok(Direction::In),
+            "out" => Ok(Direction::Out),
+            _ => Err(format!("invalid direction `{}`, expected in or out", value)),
+        }
+    }
+}
+
+/// Conditions on transactions, all of them must match. Lists match any of
+/// their values, case insensitive.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    /// First day included, in local time.
+    pub from: Option<NaiveDate>,
+    /// Last day included, in local time.
+    pub to: Option<NaiveDate>,
+    /// Minimum absolute amount.
+    pub min_amount: Option<f64>,
+    /// Maximum absolute amount.
+    pub max_amount: Option<f64>,
+    pub direction: Option<Direction>,
+    pub kinds: Vec<Kind>,
+    /// Pattern searched in the merchant name.
+    pub merchant: Option<Regex>,
+    pub mcc: Vec<String>,
+    pub categories: Vec<String>,
+    /// Merchant country name.
+    pub countries: Vec<String>,
+    /// Currency paid in, as numeric ISO 4217 code, symbol or name.
+    pub currencies: Vec<String>,
+}
+
+/// Value is one of the wanted ones, or no value is wanted.
+fn any_of(wanted: &[String], value: Option<&str>) -> bool {
+    wanted.is_empty()
+        || value.is_some_and(|value| {
+            wanted
+                .iter()
+                .any(|wanted| wanted.trim().eq_ignore_ascii_case(value.trim()))
+        })
+}
+
+impl Filter {
+    /// Case insensitive pattern of the merchant name.
+    pub fn merchant_pattern(pattern: &str) -> Result<Regex> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map_err(|err| format!("invalid merchant pattern: {}", err).into())
+    }
+
+    pub fn matches(&self, transaction: &Transaction) -> bool {
+        let date = transaction.date.with_timezone(&Local).date_naive();
+        if self.from.is_some_and(|from| date < from) || self.to.is_some_and(|to| date > to) {
+            return false;
+        }
+
+        let amount = transaction.amount.abs();
+        if self.min_amount.is_some_and(|min| amount < min)
+            || self.max_amount.is_some_and(|max| amount > max)
+        {
+            return false;
+        }
+
+        match self.direction {
+            Some(Direction::In) if transaction.amount <= 0.0 => return false,
+            Some(Direction::Out) if transaction.amount >= 0.0 => return false,
+            _ => {}
+        }
+
+        if !self.kinds.is_empty() && !self.kinds.contains(&transaction.kind) {
+            return false;
+        }
+
+        if let Some(merchant) = &self.merchant
+            && !merchant.is_match(&transaction.name)
+        {
+            return false;
+        }
+
+        let currency = transaction.currency();
+        any_of(&self.mcc, transaction.mcc.as_deref())
+            && any_of(&self.categories, transaction.category.as_deref())
+            && any_of(&self.countries, transaction.country.as_deref())
+            && (any_of(&self.currencies, currency.map(|c| c.code.as_str()))
+                || any_of(&self.currencies, currency.map(|c| c.symbol.as_str()))
+                || any_of(&self.currencies, currency.map(|c| c.name.as_str())))
+    }
+
+    /// Whether Safe executions can match, their logs are only fetched then.
+    /// Sent funds need them too, to be told apart from withdrawals.
+    pub fn needs_safe_executions(&self) -> bool {
+        self.direction != Some(Direction::In)
+            && (self.kinds.is_empty()
+                || self.kinds.iter().any(|kind| {
+                    matches!(
+                        kind,
+                        Kind::Sent | Kind::Withdrawal | Kind::SafeTransaction | Kind::SafeFailure
+                    )
+                }))
+    }
+}
@@ -1,9 +1,11 @@
+use crate::auth::Auth;
 use crate::prelude::*;
-use serde::Deserialize;
+use crate::recording::Recording;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 pub struct GnosisTransaction {
     pub created_at: String,
     pub cleared_at: Option<String>,
@@ -20,9 +22,8 @@ pub struct GnosisTransaction {
     pub status: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 pub struct Currency {
     pub symbol: String,
     pub code: String,
@@ -30,18 +31,16 @@ pub struct Currency {
     pub name: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 pub struct Merchant {
     pub name: String,
     pub city: String,
     pub country: Country,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 pub struct Country {
     pub name: String,
     pub numeric: String,
@@ -49,9 +48,8 @@ pub struct Country {
     pub alpha3: String,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-#[allow(dead_code)]
 pub struct Transaction {
     pub status: String,
     pub to: String,
@@ -60,49 +58,201 @@ pub struct Transaction {
     pub hash: String,
 }
 
+/// Errors returned by Gnosis Pay.
+#[derive(Debug)]
+pub enum GnosisPayError {
+    /// The session token expired or is invalid.
+    SessionExpired,
+    RequestFailed(reqwest::StatusCode),
+    InvalidResponse,
+}
+
+impl fmt::Display for GnosisPayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GnosisPayError::SessionExpired => write!(
+                f,
+                "Gnosis Pay session expired or invalid, run `login` again or update SESSION_TOKEN"
+            ),
+            GnosisPayError::RequestFailed(status) => {
+                write!(f, "Gnosis Pay request failed: {}", status)
+            }
+            GnosisPayError::InvalidResponse => write!(f, "Gnosis Pay returned an invalid response"),
+        }
+    }
+}
+
+impl std::error::Error for GnosisPayError {}
+
+impl GnosisPayError {
+    fn from_status(status: reqwest::StatusCode) -> Self {
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                GnosisPayError::SessionExpired
+            }
+            status => GnosisPayError::RequestFailed(status),
+        }
+    }
+}
+
+/// A page of transactions from the Gnosis Pay API.
+#[derive(Debug, Clone, Deserialize)]
+#[allow(dead_code)]
+struct TransactionsPage {
+    count: u64,
+    next: Option<String>,
+    results: Vec<GnosisTransaction>,
+}
+
+/// Gnosis Pay client, for the web app with a session cookie or the API with a JWT.
 pub struct GnosisPay {
     base_url: String,
-    session_token: String,
+    api_url: String,
+    auth: Auth,
+    client: reqwest::Client,
+    recording: Option<Recording>,
 }
 
 impl GnosisPay {
+    /// Number of transactions per page on the Gnosis Pay API.
+    const PAGE_SIZE: usize = 100;
+    /// Default web app endpoint.
+    pub const BASE_URL: &str = "https://app.gnosispay.com";
+    /// Default API endpoint.
+    pub const API_URL: &str = "https://api.gnosispay.com";
+    /// Recorded response of the web app.
+    const APP_RECORDING: &str = "gnosispay_app_transactions";
+
     pub fn new(session_token: String) -> Self {
+        Self::with_auth(Auth::Cookie(session_token))
+    }
+
+    pub fn with_auth(auth: Auth) -> Self {
         Self {
-            base_url: String::from("https://app.gnosispay.com"),
-            session_token,
+            base_url: String::from(Self::BASE_URL),
+            api_url: String::from(Self::API_URL),
+            auth,
+            client: reqwest::Client::new(),
+            recording: None,
         }
     }
 
+    /// Serve recorded responses, whichever session they were recorded with.
+    pub fn replay(recording: Recording) -> Self {
+        let auth = if recording.contains(Self::APP_RECORDING) {
+            Auth::Cookie(String::new())
+        } else {
+            Auth::Bearer(String::new())
+        };
+        Self::with_auth(auth).with_recording(Some(recording))
+    }
+
+    /// Use another web app endpoint, used with a session cookie.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Use another API endpoint, used with a JWT.
+    pub fn with_api_url(mut self, api_url: String) -> Self {
+        self.api_url = api_url;
+        self
+    }
+
+    /// Share a client, and its connection pool, with other services.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Record responses to, or replay them from, a directory.
+    pub fn with_recording(mut self, recording: Option<Recording>) -> Self {
+        self.recording = recording;
+        self
+    }
+
     /// Retrieve all transactions from GnosisPay.
     pub async fn retrieve_transactions(&self) -> Result<Vec<GnosisTransaction>> {
+        match &self.auth {
+            Auth::Cookie(session_token) => self.retrieve_app_transactions(session_token).await,
+            Auth::Bearer(token) => self.retrieve_api_transactions(token).await,
+        }
+    }
+
+    /// Retrieve transactions from the web app, using the session cookie.
+    async fn retrieve_app_transactions(
+        &self,
+        session_token: &str,
+    ) -> Result<Vec<GnosisTransaction>> {
         let url = format!("{}/api/v1/transactions", self.base_url);
 
-        let client = reqwest::Client::new();
+        let cookie = format!("__Secure-authjs.session-token=\"{}\"", session_token);
+
+        let body = Recording::fetch(self.recording.as_ref(), Self::APP_RECORDING, async {
+            let response = self
+                .client
+                .get(&url)
+                .header("Cookie", cookie.as_str())
+                .send()
+                .await?;
+
+            match response.status() {
+                reqwest::StatusCode::OK => Ok(response.text().await?),
+                status => Err(GnosisPayError::from_status(status).into()),
+            }
+        })
+        .await?;
+
+        let tx_response = match serde_json::from_str::<Vec<GnosisTransaction>>(&body) {
+            Ok(tx_response) => tx_response,
+            Err(err) => {
+                eprintln!("{:?}", err);
+                return Err(GnosisPayError::InvalidResponse.into());
+            }
+        };
+
+        Ok(tx_response)
+    }
+
+    /// Retrieve transactions from the public API, using the JWT from `login`.
+    async fn retrieve_api_transactions(&self, token: &str) -> Result<Vec<GnosisTransaction>> {
+        let mut transactions = Vec::new();
 
-        let cookie = format!(
-            "__Secure-authjs.session-token=\"{}\"",
-            self.session_token.clone()
-        );
+        loop {
+            let url = format!(
+                "{}/api/v1/cards/transactions?limit={}&offset={}",
+                self.api_url,
+                Self::PAGE_SIZE,
+                transactions.len()
+            );
 
-        let response = client
-            .get(&url)
-            .header("Cookie", cookie.as_str())
-            .send()
+            let name = format!("gnosispay_api_transactions_{}", transactions.len());
+            let body = Recording::fetch(self.recording.as_ref(), &name, async {
+                let response = self.client.get(&url).bearer_auth(token).send().await?;
+
+                match response.status() {
+                    reqwest::StatusCode::OK => Ok(response.text().await?),
+                    status => Err(GnosisPayError::from_status(status).into()),
+                }
+            })
             .await?;
 
-        let tx_response = match response.status() {
-            reqwest::StatusCode::OK => match response.json::<Vec<GnosisTransaction>>().await {
-                Ok(tx_response) => tx_response,
+            let page = match serde_json::from_str::<TransactionsPage>(&body) {
+                Ok(page) => page,
                 Err(err) => {
                     eprintln!("{:?}", err);
-                    return Err("Invalid response".into());
+                    return Err(GnosisPayError::InvalidResponse.into());
                 }
-            },
-            _ => {
-                return Err("Request failed".into());
+            };
+
+            let last_page = page.next.is_none() || page.results.is_empty();
+            transactions.extend(page.results);
+
+            if last_page {
+                break;
             }
-        };
+        }
 
-        Ok(tx_response)
+        Ok(transactions)
     }
 }
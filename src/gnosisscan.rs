@@ -1,11 +1,11 @@
 extern crate serde;
 extern crate serde_json;
-use crate::monerium::EURE_V2_ADDRESS;
+use crate::monerium::Token;
 use crate::prelude::*;
+use crate::recording::Recording;
 use serde::Deserialize;
 
 #[derive(Debug, Clone, Deserialize)]
-#[allow(dead_code)]
 pub struct Response {
     pub status: String,
     pub message: String,
@@ -13,7 +13,6 @@ pub struct Response {
 }
 
 #[derive(Debug, Clone, Deserialize)]
-#[allow(dead_code)]
 pub struct TokenTxResponse {
     #[serde(rename = "blockNumber")]
     pub block_number: String,
@@ -41,42 +40,161 @@ pub struct TokenTxResponse {
     pub confirmations: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogsResponse {
+    pub status: String,
+    pub message: String,
+    pub result: Option<Vec<LogResponse>>,
+}
+
+/// An event log, numbers are hexadecimal.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LogResponse {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    #[serde(rename = "blockNumber")]
+    pub block_number: String,
+    #[serde(rename = "timeStamp")]
+    pub timestamp: String,
+    #[serde(rename = "transactionHash")]
+    pub transaction_hash: String,
+}
+
+/// Most logs Gnosisscan returns per request.
+const LOGS_PAGE_SIZE: usize = 1000;
+
+/// Gnosisscan API client.
 pub struct Gnosisscan {
     base_url: String,
     api_key: String,
+    client: reqwest::Client,
+    recording: Option<Recording>,
 }
 
 impl Gnosisscan {
+    /// Default API endpoint.
+    pub const BASE_URL: &str = "https://api.gnosisscan.io";
+
     pub fn new(api_key: String) -> Self {
         Self {
-            base_url: String::from("https://api.gnosisscan.io"),
+            base_url: String::from(Self::BASE_URL),
             api_key,
+            client: reqwest::Client::new(),
+            recording: None,
         }
     }
 
-    /// Retrieve all transactions from a wallet address to EURe V2.
-    pub async fn retrieve_eure_transactions(&self, wallet_address: String) -> Result<Response> {
+    /// Use another endpoint, e.g. a proxy or a compatible explorer.
+    pub fn with_base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Share a client, and its connection pool, with other services.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Record responses to, or replay them from, a directory.
+    pub fn with_recording(mut self, recording: Option<Recording>) -> Self {
+        self.recording = recording;
+        self
+    }
+
+    /// Retrieve all transactions from a wallet address to the token.
+    pub async fn retrieve_eure_transactions(
+        &self,
+        wallet_address: String,
+        token: Token,
+    ) -> Result<Response> {
         let url = format!(
             "{}/api?module=account&action=tokentx&address={}&contractaddress={}&sort=asc&apikey={}",
-            self.base_url, wallet_address, EURE_V2_ADDRESS, self.api_key
+            self.base_url,
+            wallet_address,
+            token.address(),
+            self.api_key
         );
 
-        let client = reqwest::Client::new();
-        let response = client.get(&url).send().await?;
-
-        let tokentx_response = match response.status() {
-            reqwest::StatusCode::OK => match response.json::<Response>().await {
-                Ok(tokentx_response) => tokentx_response,
-                Err(err) => {
-                    eprintln!("{}", err);
-                    return Err("Invalid response".into());
-                }
-            },
-            _ => {
-                return Err("Request failed".into());
+        let name = format!("gnosisscan_{}_{:?}", wallet_address, token).to_lowercase();
+        let body = Recording::fetch(self.recording.as_ref(), &name, async {
+            let response = self.client.get(&url).send().await?;
+            match response.status() {
+                reqwest::StatusCode::OK => Ok(response.text().await?),
+                _ => Err("Request failed".into()),
+            }
+        })
+        .await?;
+
+        let tokentx_response = match serde_json::from_str::<Response>(&body) {
+            Ok(tokentx_response) => tokentx_response,
+            Err(err) => {
+                eprintln!("{}", err);
+                return Err("Invalid response".into());
             }
         };
 
         Ok(tokentx_response)
     }
+
+    /// Retrieve all events emitted by the Safe of a wallet, page by page.
+    pub async fn retrieve_safe_logs(&self, wallet_address: String) -> Result<LogsResponse> {
+        let mut logs = Vec::new();
+        let mut page = 1;
+        loop {
+            let response = self.retrieve_safe_logs_page(&wallet_address, page).await?;
+            let Some(result) = response.result else {
+                return Ok(response);
+            };
+
+            // A short page is the last one.
+            let count = result.len();
+            logs.extend(result);
+            if count < LOGS_PAGE_SIZE {
+                return Ok(LogsResponse {
+                    status: response.status,
+                    message: response.message,
+                    result: Some(logs),
+                });
+            }
+            page += 1;
+        }
+    }
+
+    async fn retrieve_safe_logs_page(
+        &self,
+        wallet_address: &str,
+        page: usize,
+    ) -> Result<LogsResponse> {
+        let url = format!(
+            "{}/api?module=logs&action=getLogs&address={}&fromBlock=0&toBlock=latest&page={}&offset={}&apikey={}",
+            self.base_url, wallet_address, page, LOGS_PAGE_SIZE, self.api_key
+        );
+
+        // The first page keeps the name of recordings made before pagination.
+        let name = match page {
+            1 => format!("gnosisscan_{}_logs", wallet_address),
+            page => format!("gnosisscan_{}_logs_{}", wallet_address, page),
+        }
+        .to_lowercase();
+        let body = Recording::fetch(self.recording.as_ref(), &name, async {
+            let response = self.client.get(&url).send().await?;
+            match response.status() {
+                reqwest::StatusCode::OK => Ok(response.text().await?),
+                _ => Err("Request failed".into()),
+            }
+        })
+        .await?;
+
+        let logs_response = match serde_json::from_str::<LogsResponse>(&body) {
+            Ok(logs_response) => logs_response,
+            Err(err) => {
+                eprintln!("{}", err);
+                return Err("Invalid response".into());
+            }
+        };
+
+        Ok(logs_response)
+    }
 }
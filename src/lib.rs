@@ -0,0 +1,65 @@
+//! Gnosis Pay client library.
+//!
+//! Retrieve on-chain EURe history from Gnosisscan, card payment details from
+//! Gnosis Pay, merge them into [`Transactions`], and [`monitor()`] wallets for
+//! new card payments with Pushover notifications.
+//!
+//! ```no_run
+//! use gnosispay::{Auth, GnosisPay, Gnosisscan, Token, Transactions};
+//!
+//! # async fn run() -> gnosispay::Result<()> {
+//! let client = reqwest::Client::new();
+//! let wallet = String::from("0x14791697260E4c9A71f18484C9f997B308e59325");
+//!
+//! let response = Gnosisscan::new(String::from("API_KEY"))
+//!     .with_client(client.clone())
+//!     .retrieve_eure_transactions(wallet.clone(), Token::Eure)
+//!     .await?;
+//! let payments = GnosisPay::with_auth(Auth::Bearer(String::from("JWT")))
+//!     .with_client(client)
+//!     .retrieve_transactions()
+//!     .await?;
+//!
+//! let mut transactions = Transactions::new(wallet)?;
+//! transactions.import_from_gnosisscan(response)?;
+//! transactions.merge_description_from_gnosispay(payments)?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod address_book;
+pub mod annotations;
+pub mod api;
+pub mod auth;
+pub mod budget;
+pub mod error;
+pub mod filter;
+pub mod gnosispay;
+pub mod gnosisscan;
+pub mod mcc;
+pub mod metrics;
+pub mod monerium;
+pub mod monitor;
+pub mod mqtt;
+pub mod notify;
+pub mod prelude;
+pub mod recording;
+pub mod report;
+pub mod rules;
+pub mod safe;
+pub mod secrets;
+pub mod subscriptions;
+pub mod summary;
+pub mod transactions;
+pub mod wallets;
+
+pub use crate::auth::{Auth, Session, SiweLogin};
+pub use crate::error::Error;
+pub use crate::gnosispay::{GnosisPay, GnosisPayError, GnosisTransaction};
+pub use crate::gnosisscan::Gnosisscan;
+pub use crate::monerium::Token;
+pub use crate::monitor::{MonitorOptions, Wallet, monitor};
+pub use crate::notify::{Message, Priority, PushOverNotify};
+pub use crate::prelude::Result;
+pub use crate::recording::Recording;
+pub use crate::transactions::{CsvOptions, Transaction, Transactions};
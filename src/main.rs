@@ -1,42 +1,564 @@
 mod args;
-mod error;
-mod gnosispay;
-mod gnosisscan;
-mod monerium;
-mod monitor;
-mod notify;
-mod prelude;
-mod transactions;
-
-use crate::args::CmdArgs;
-use crate::gnosispay::GnosisPay;
-use crate::monitor::monitor;
-use crate::prelude::*;
+mod config;
+mod tui;
+
+use crate::args::{
+    AnnotateArgs, CmdArgs, ConfigArgs, ConfigSubcommand, ExportFormat, LimitsArgs, ListArgs,
+    LoginArgs, OnchainArgs, ReportFormat, SafeArgs, SafeSubcommand, SecretsArgs, SecretsSubcommand,
+};
+use crate::config::{Config, Profile};
+use alloy::primitives::{U256, utils::format_units};
+use alloy::providers::ProviderBuilder;
+use alloy::signers::local::PrivateKeySigner;
+use chrono::{Local, Utc};
 use clap::Parser;
-use gnosisscan::Gnosisscan;
-use transactions::Transactions;
+use colored::Colorize;
+use gnosispay::address_book::AddressBook;
+use gnosispay::annotations::{Annotations, Split};
+use gnosispay::api::{self, Store};
+use gnosispay::auth::{Auth, Session, SiweLogin};
+use gnosispay::budget::{Budget, Progress};
+use gnosispay::filter::Filter;
+use gnosispay::gnosispay::{GnosisPay, GnosisPayError};
+use gnosispay::gnosisscan::Gnosisscan;
+use gnosispay::metrics::{self, Metrics};
+use gnosispay::monerium::Token;
+use gnosispay::monitor::{BalanceThresholds, MonitorOptions, Wallet, monitor};
+use gnosispay::mqtt::{self, Mqtt};
+use gnosispay::notify::{Message, Priority, PushOverNotify};
+use gnosispay::prelude::*;
+use gnosispay::recording::Recording;
+use gnosispay::report::Report;
+use gnosispay::rules::Rules;
+use gnosispay::safe::{Allowance, DelayQueue, SafeInfo};
+use gnosispay::secrets::{self, Secrets};
+use gnosispay::subscriptions::Subscription;
+use gnosispay::summary::Summary;
+use gnosispay::transactions::{CsvOptions, Kind, Transactions};
+use gnosispay::wallets::{WalletConfig, Wallets};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Ask the secrets passphrase on the terminal.
+fn prompt_passphrase() -> Result<String> {
+    Ok(rpassword::prompt_password("Secrets passphrase: ")?)
+}
+
+/// Use the session token when given or stored, otherwise the session from `login`.
+fn gnosispay_auth(session_token: Option<String>) -> Result<Auth> {
+    let auth = match session_token {
+        Some(session_token) => Auth::Cookie(session_token),
+        None => match Secrets::lookup(secrets::SESSION_TOKEN)? {
+            Some(session_token) => Auth::Cookie(session_token),
+            None => Session::load()?.auth,
+        },
+    };
+
+    // Warn before the session expires, rather than failing later.
+    if let Some(expires_at) = auth.expires_at() {
+        let left = expires_at - chrono::Utc::now();
+
+        if left <= chrono::Duration::zero() {
+            return Err(GnosisPayError::SessionExpired.into());
+        }
+        if left < SESSION_EXPIRY_WARNING {
+            eprintln!(
+                "{}",
+                format!(
+                    "[!] Gnosis Pay session expires in {} minutes, run `login` again.",
+                    left.num_minutes()
+                )
+                .yellow()
+            );
+        }
+    }
+
+    Ok(auth)
+}
+
+/// Manage the encrypted credentials.
+fn manage_secrets(args: SecretsArgs) -> Result<()> {
+    let mut secrets = Secrets::open()?;
+
+    match args.cmd {
+        SecretsSubcommand::Set { name } => {
+            let value = rpassword::prompt_password(format!("Value for `{}`: ", name))?;
+            secrets.set(&name, value);
+            let path = secrets.save()?;
+            println!(
+                "{}",
+                format!("[+] `{}` stored in `{}`.", name, path.display()).green()
+            );
+        }
+        SecretsSubcommand::Remove { name } => {
+            if secrets.remove(&name).is_none() {
+                return Err(format!("`{}` is not stored", name).into());
+            }
+            secrets.save()?;
+            println!("{}", format!("[+] `{}` removed.", name).green());
+        }
+        SecretsSubcommand::List => {
+            for name in secrets.names() {
+                println!("{}", name);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sign in with the Safe owner key and store the session.
+async fn login(args: LoginArgs, client: reqwest::Client) -> Result<()> {
+    let signer = match (args.private_key, args.keystore) {
+        (Some(private_key), _) => private_key.parse::<PrivateKeySigner>()?,
+        (None, Some(keystore)) => {
+            let password = match args.keystore_password {
+                Some(password) => password,
+                None => rpassword::prompt_password("Keystore password: ")?,
+            };
+            PrivateKeySigner::decrypt_keystore(keystore, password)?
+        }
+        (None, None) => return Err("a private key or keystore is required".into()),
+    };
+
+    println!("[+] Signing in as `{}`", signer.address());
+
+    let session = SiweLogin::new(args.api_url, args.domain, args.uri)
+        .with_client(client)
+        .login(&signer)
+        .await?;
+    let path = session.save()?;
+
+    println!(
+        "{}",
+        format!("[+] Session stored in `{}`.", path.display()).green()
+    );
+    Ok(())
+}
+
+/// Convert a token amount to a decimal number.
+fn to_amount(value: U256, token: Token) -> Result<f64> {
+    Ok(format_units(value, token.decimals())?.parse::<f64>()?)
+}
+
+/// Read the card spending allowance of a wallet from chain.
+async fn spending_allowance(
+    wallet_address: &str,
+    rpc_url: Option<String>,
+) -> Result<Option<Allowance>> {
+    let rpc_url = rpc_url.unwrap_or_else(|| String::from(OnchainArgs::RPC_URL));
+    let provider = ProviderBuilder::new().connect(&rpc_url).await?;
+    Allowance::of_safe(&provider, wallet_address.parse()?).await
+}
+
+/// Show the card spending allowance.
+async fn limits(args: LimitsArgs) -> Result<()> {
+    let Some(wallet_address) = args.wallet_address else {
+        return Err("a wallet address is required, set WALLET_ADDRESS or use a profile".into());
+    };
+    let token = args.token.unwrap_or_default();
+
+    let Some(allowance) = spending_allowance(&wallet_address, args.rpc_url).await? else {
+        return Err("no Roles module enabled on the Safe, no spending allowance".into());
+    };
+
+    let now = Utc::now();
+    println!(
+        "{}: {:.2} {} every {}h, up to {:.2}",
+        "Allowance".blue(),
+        to_amount(allowance.refill, token)?,
+        token,
+        allowance.period / 3600,
+        to_amount(allowance.max_refill, token)?
+    );
+    println!(
+        "{}: {:.2} {}",
+        "Remaining".blue(),
+        to_amount(allowance.remaining(now), token)?,
+        token
+    );
+    if let Some(next_refill) = allowance.next_refill(now) {
+        println!(
+            "{}: {}",
+            "Next refill".blue(),
+            next_refill
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+        );
+    }
+    Ok(())
+}
+
+/// Read the Safe configuration over RPC, recipients named after the address book.
+async fn safe(args: SafeArgs, address_book: &AddressBook) -> Result<()> {
+    let Some(wallet_address) = args.wallet_address else {
+        return Err("a wallet address is required, set WALLET_ADDRESS or use a profile".into());
+    };
+    let rpc_url = args
+        .rpc_url
+        .unwrap_or_else(|| String::from(OnchainArgs::RPC_URL));
+
+    let provider = ProviderBuilder::new().connect(&rpc_url).await?;
+
+    match args.cmd {
+        SafeSubcommand::Info => {
+            let info = SafeInfo::load(&provider, wallet_address.parse()?).await?;
+            println!("{}", info);
+        }
+        SafeSubcommand::Queue => {
+            let info = SafeInfo::load(&provider, wallet_address.parse()?).await?;
+            let modules = info.delay_modules();
+            if modules.is_empty() {
+                return Err("no Delay module enabled on the Safe".into());
+            }
+
+            for module in modules {
+                let queue = DelayQueue::load(&provider, module).await?;
+                println!("{}", queue.with_address_book(address_book.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Connect to the MQTT broker when one is set. The password is only looked
+/// up for a username given outside the URL.
+fn mqtt(args: &OnchainArgs) -> Result<Option<Mqtt>> {
+    let Some(url) = &args.mqtt_url else {
+        return Ok(None);
+    };
+
+    let credentials = match &args.mqtt_username {
+        Some(username) => Some((
+            username.clone(),
+            Secrets::resolve(args.mqtt_password.clone(), secrets::MQTT_PASSWORD)?,
+        )),
+        None => None,
+    };
+
+    let mqtt = Mqtt::connect(
+        url,
+        credentials,
+        args.mqtt_topic_prefix
+            .clone()
+            .unwrap_or_else(|| String::from(mqtt::TOPIC_PREFIX)),
+        args.mqtt_discovery_prefix
+            .clone()
+            .unwrap_or_else(|| String::from(mqtt::DISCOVERY_PREFIX)),
+    )?;
+    println!(
+        "{}",
+        format!("[+] Publishing to MQTT on `{}`", mqtt.broker()).green()
+    );
+
+    Ok(Some(mqtt))
+}
+
+/// Check the budgets of the profile before using them.
+fn budgets(profile: &Profile) -> Result<Vec<Budget>> {
+    for budget in &profile.budgets {
+        budget.validate()?;
+    }
+    Ok(profile.budgets.clone())
+}
+
+/// Show spending against each budget in the current period.
+fn show_budgets(budgets: &[Budget], transactions: &Transactions) {
+    let progress = Progress::of(budgets, transactions.transactions.values(), &Local::now());
+    let width = budgets
+        .iter()
+        .map(|budget| budget.name().chars().count())
+        .max()
+        .unwrap_or_default();
+
+    for progress in progress {
+        println!(
+            "{}  {}",
+            format!("{:<width$}", progress.budget.name()).blue(),
+            progress
+        );
+    }
+}
+
+/// List subscriptions with their next charge.
+fn show_subscriptions(transactions: &Transactions) {
+    // Lapsed subscriptions were not charged within their cadence, e.g. cancelled.
+    let now = Utc::now();
+    let (lapsed, subscriptions): (Vec<_>, Vec<_>) =
+        Subscription::detect(transactions.transactions.values())
+            .into_iter()
+            .partition(|subscription| subscription.is_lapsed(now));
+    if subscriptions.is_empty() && lapsed.is_empty() {
+        println!("No subscription found.");
+        return;
+    }
+
+    if !subscriptions.is_empty() {
+        let width = subscriptions
+            .iter()
+            .map(|subscription| subscription.merchant.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max("Merchant".len());
+
+        println!(
+            "{}",
+            format!(
+                "{:<width$}  {:<10}  {:>8}  {:>7}  {:<10}  {:<10}",
+                "Merchant", "Cadence", "Amount", "Charges", "Last", "Next"
+            )
+            .blue()
+        );
+        for subscription in &subscriptions {
+            println!("{:<width$}  {}", subscription.merchant, subscription);
+        }
+    }
+
+    if !lapsed.is_empty() {
+        let merchants: Vec<_> = lapsed
+            .iter()
+            .map(|subscription| subscription.merchant.as_str())
+            .collect();
+        println!("\n{}: {}", "Lapsed".blue(), merchants.join(", "));
+    }
+}
+
+/// Change the annotation of a transaction, then show it.
+fn annotate(args: AnnotateArgs) -> Result<()> {
+    let hash = args.hash.trim();
+    let is_hash = hash.len() == 66
+        && hash.starts_with("0x")
+        && hash[2..].chars().all(|c| c.is_ascii_hexdigit());
+    if !is_hash {
+        return Err(format!("invalid transaction hash `{}`", hash).into());
+    }
+
+    let mut annotations = Annotations::open()?;
+    let annotation = annotations.entry(hash);
+    for tag in &args.tag {
+        annotation.tag(tag);
+    }
+    for tag in &args.untag {
+        annotation.untag(tag);
+    }
+    if let Some(note) = &args.note {
+        annotation.set_note(note);
+    }
+    for receipt in &args.receipt {
+        let receipt = std::fs::canonicalize(receipt)
+            .map_err(|err| format!("cannot read receipt `{}`: {}", receipt.display(), err))?;
+        annotation.attach(receipt);
+    }
+    for receipt in &args.remove_receipt {
+        annotation.detach(&std::fs::canonicalize(receipt).unwrap_or_else(|_| receipt.clone()));
+    }
+    let annotation = annotation.clone();
+
+    let changed = !args.tag.is_empty()
+        || !args.untag.is_empty()
+        || args.note.is_some()
+        || !args.receipt.is_empty()
+        || !args.remove_receipt.is_empty();
+    if changed {
+        let path = annotations.save()?;
+        println!(
+            "{}",
+            format!("[+] Annotations saved to `{}`.", path.display()).green()
+        );
+    }
+
+    if annotation.is_empty() {
+        println!("No annotation.");
+    } else {
+        print!("{}", annotation);
+    }
+    Ok(())
+}
+
+/// Replace the splits of a transaction, then show them.
+fn split(hash: &str, parts: Vec<Split>, clear: bool, transactions: &Transactions) -> Result<()> {
+    let hash = hash.trim();
+    let Some(transaction) = transactions
+        .transactions
+        .values()
+        .find(|transaction| transaction.hash.eq_ignore_ascii_case(hash))
+    else {
+        return Err(format!("no transaction `{}`, check the filters", hash).into());
+    };
+
+    let mut annotations = Annotations::open()?;
+    let annotation = annotations.entry(&transaction.hash);
+    if clear {
+        annotation.splits.clear();
+    } else {
+        annotation.split(parts, transaction.amount)?;
+    }
+    let splits = annotation.splits.clone();
+
+    let path = annotations.save()?;
+    println!(
+        "{}",
+        format!("[+] Annotations saved to `{}`.", path.display()).green()
+    );
+
+    if splits.is_empty() {
+        println!("No split.");
+    }
+    for split in splits {
+        println!("{}: {}", "Split".blue(), split);
+    }
+    Ok(())
+}
+
+/// List card payments without a receipt, oldest first.
+fn show_missing_receipts(transactions: &Transactions) {
+    let mut missing: Vec<_> = transactions
+        .transactions
+        .values()
+        .filter(|transaction| {
+            transaction.kind == Kind::Card
+                && transaction.amount < 0.0
+                && transaction.annotation.receipts.is_empty()
+        })
+        .collect();
+    if missing.is_empty() {
+        println!("No receipt missing.");
+        return;
+    }
+    missing.sort_by_key(|transaction| transaction.date);
+
+    let width = missing
+        .iter()
+        .map(|transaction| transaction.name.chars().count())
+        .max()
+        .unwrap_or_default()
+        .max("Merchant".len());
+
+    println!(
+        "{}",
+        format!(
+            "{:<10}  {:>8}  {:<width$}  {}",
+            "Date", "Amount", "Merchant", "Hash"
+        )
+        .blue()
+    );
+    for transaction in &missing {
+        println!(
+            "{:<10}  {:>8.2}  {:<width$}  {}",
+            transaction.date.with_timezone(&Local).format("%Y-%m-%d"),
+            -transaction.amount,
+            transaction.name,
+            transaction.hash
+        );
+    }
+
+    let total = missing
+        .iter()
+        .fold(0.0, |total, transaction| total - transaction.amount);
+    println!(
+        "\n{}: {} card payment(s), {:.2} EUR",
+        "Missing receipts".blue(),
+        missing.len(),
+        total
+    );
+}
+
+/// Print the configuration, secrets are redacted.
+fn show_config(
+    args: ConfigArgs,
+    path: &std::path::Path,
+    config: &Config,
+    profile_name: Option<String>,
+    profile: Profile,
+) -> Result<()> {
+    match args.cmd {
+        ConfigSubcommand::Path => println!("{}", path.display()),
+        ConfigSubcommand::Show { all } => {
+            println!("[+] Configuration `{}`", path.display());
+
+            if all {
+                let mut config = config.clone();
+                for profile in config.profiles.values_mut() {
+                    *profile = profile.redacted();
+                }
+                println!("\n{}", toml::to_string_pretty(&config)?);
+            } else {
+                match profile_name {
+                    Some(name) => println!("[+] Profile `{}`", name),
+                    None => println!("[+] No profile selected"),
+                }
+                println!("\n{}", toml::to_string_pretty(&profile.redacted())?);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Warn when the session expires within this delay.
+const SESSION_EXPIRY_WARNING: chrono::Duration = chrono::Duration::hours(24);
 
 /// Load transactions from Gnosisscan and merge description with Gnosispay.
 async fn load_transactions(
-    wallet_address: String,
-    session_token: String,
-    gnosisscan_api_key: String,
+    args: ListArgs,
+    client: &reqwest::Client,
+    recording: Option<Recording>,
+    profile: &Profile,
+    filter: &Filter,
 ) -> Result<Transactions> {
+    let wallet_address = args.wallet_address()?;
+
+    // Credentials are not needed to replay responses.
+    let gnosisscan_api_key = if matches!(recording, Some(Recording::Replay(_))) {
+        args.gnosisscan_api_key.unwrap_or_default()
+    } else {
+        Secrets::resolve(args.gnosisscan_api_key, secrets::GNOSISSCAN_API_KEY)?
+    };
+
     // All transactions.
-    let mut transactions = Transactions::new(wallet_address.clone().into())?;
+    let mut transactions =
+        Transactions::new(wallet_address.clone())?.with_address_book(profile.addresses.clone());
 
     // Importing transactions from Gnosis Scan.
-    let gnosisscan = Gnosisscan::new(gnosisscan_api_key.into());
+    let gnosisscan = Gnosisscan::new(gnosisscan_api_key)
+        .with_client(client.clone())
+        .with_recording(recording.clone());
     let response = gnosisscan
-        .retrieve_eure_transactions(wallet_address.clone().into())
+        .retrieve_eure_transactions(wallet_address.clone(), args.token.unwrap_or_default())
         .await?;
 
     transactions.import_from_gnosisscan(response)?;
 
+    // Safe transactions signed by the owners, unless filtered out.
+    if filter.needs_safe_executions() {
+        let response = gnosisscan
+            .retrieve_safe_logs(wallet_address.clone())
+            .await?;
+        transactions.import_safe_executions(response)?;
+    }
+
     // Importing transaction's description from Gnosis Pay.
-    let gnosispay = GnosisPay::new(session_token.into());
+    let gnosispay = match recording {
+        Some(recording @ Recording::Replay(_)) => GnosisPay::replay(recording),
+        recording => GnosisPay::with_auth(gnosispay_auth(args.session_token)?)
+            .with_client(client.clone())
+            .with_recording(recording),
+    };
     let gnosis_transactions = gnosispay.retrieve_transactions().await?;
     transactions.merge_description_from_gnosispay(gnosis_transactions)?;
+    transactions.categorize(&profile.categories);
+    // Annotations are only shown here, a listing is still useful without them.
+    let annotations = Annotations::open().unwrap_or_else(|err| {
+        eprintln!(
+            "{}",
+            format!(
+                "[-] Cannot read the annotations, listing without them: {}",
+                err
+            )
+            .yellow()
+        );
+        Annotations::default()
+    });
+    transactions.annotate(&annotations);
 
     Ok(transactions)
 }
@@ -47,53 +569,388 @@ async fn main() -> Result<()> {
 
     // Parsing command line.
     let args = CmdArgs::parse();
+    secrets::prompt_with(prompt_passphrase);
 
     // Do we want colors?
     if args.no_color {
         colored::control::set_override(false);
     }
 
+    // Settings from the configuration file, overridden by flags and environment.
+    let (config_path, config) = Config::load(args.config.as_deref())?;
+    let (profile_name, profile) = config.profile(args.profile.as_deref())?;
+
+    // A single HTTP client shared by all services.
+    let client = reqwest::Client::new();
+    let recording = args.recording();
+
     // Execute the proper command.
     match args.cmd {
-        args::CmdSubcommand::List(args) => {
-            let transactions = load_transactions(
-                args.wallet_address,
-                args.session_token,
-                args.gnosisscan_api_key,
+        args::CmdSubcommand::List(mut args) => {
+            args.list.merge(&profile);
+            let filter = args.filter.filter()?;
+            let mut transactions = load_transactions(
+                args.list.clone(),
+                &client,
+                recording.clone(),
+                &profile,
+                &filter,
             )
             .await?;
+            transactions.retain(&filter);
             println!("{}", transactions);
+            println!("{}: {:.2}", "Total".blue(), transactions.total());
+
+            // The allowance is read from chain, skip it when offline.
+            if !matches!(recording, Some(Recording::Replay(_))) {
+                let token = args.list.token.unwrap_or_default();
+                match spending_allowance(&args.list.wallet_address()?, args.list.rpc_url).await {
+                    Ok(Some(allowance)) => println!(
+                        "{}: {:.2} {}",
+                        "Remaining allowance today".blue(),
+                        to_amount(allowance.remaining(Utc::now()), token)?,
+                        token
+                    ),
+                    Ok(None) => {}
+                    Err(err) => eprintln!(
+                        "{}",
+                        format!("[-] Cannot read the spending allowance: {}", err).yellow()
+                    ),
+                }
+            }
         }
 
-        args::CmdSubcommand::Export(args) => {
+        args::CmdSubcommand::Export(mut args) => {
+            args.list.merge(&profile);
+            let Some(filename) = args.filename.or(profile.export.filename.clone()) else {
+                return Err("a filename is required, pass it or set `export.filename`".into());
+            };
+
+            let defaults = CsvOptions::default();
+            let options = CsvOptions {
+                delimiter: profile.export.delimiter.unwrap_or(defaults.delimiter),
+                date_format: profile
+                    .export
+                    .date_format
+                    .clone()
+                    .unwrap_or(defaults.date_format),
+            };
+
+            let filter = args.filter.filter()?;
+            let mut transactions =
+                load_transactions(args.list, &client, recording.clone(), &profile, &filter).await?;
+            transactions.retain(&filter);
+            match args.format {
+                ExportFormat::Csv => transactions.to_csv(filename, &options)?,
+                ExportFormat::Qif => transactions.to_qif(filename)?,
+                ExportFormat::Ofx => transactions.to_ofx(filename)?,
+                ExportFormat::Ledger => transactions.to_ledger(filename)?,
+            }
+        }
+
+        args::CmdSubcommand::Verify(mut args) => {
+            args.list.merge(&profile);
+            let filter = args.filter.filter()?;
+            let mut transactions =
+                load_transactions(args.list, &client, recording.clone(), &profile, &filter).await?;
+            transactions.retain(&filter);
+            transactions.verify();
+        }
+
+        args::CmdSubcommand::Monitor(mut args) => {
+            args.merge(&profile)?;
+            let wallets = match (&args.wallets, &args.wallet_address) {
+                (Some(filename), _) => Wallets::load(filename)?.wallets,
+                (None, Some(wallet_address)) => vec![WalletConfig {
+                    label: String::from("Gnosis Pay"),
+                    address: wallet_address.clone(),
+                    pushover_user: None,
+                    pushover_device: None,
+                    session_token: None,
+                    min_eure_balance: None,
+                    min_xdai_balance: None,
+                }],
+                (None, None) => return Err("no wallet to monitor".into()),
+            };
+
+            let pushover_token =
+                Secrets::resolve(args.pushover_token.clone(), secrets::PUSHOVER_TOKEN)?;
+            let default_pushover_user = match args.pushover_user.clone() {
+                Some(pushover_user) => Some(pushover_user),
+                None => Secrets::lookup(secrets::PUSHOVER_USER)?,
+            };
+
+            // Each wallet falls back to the command line settings.
+            let mut monitored = Vec::new();
+            for wallet in wallets {
+                let Some(pushover_user) = wallet.pushover_user.or(default_pushover_user.clone())
+                else {
+                    return Err(format!("no Pushover user for `{}`", wallet.label).into());
+                };
+
+                let pushover = PushOverNotify::new(pushover_user, pushover_token.clone())
+                    .with_client(client.clone())
+                    .with_device(wallet.pushover_device.or(args.pushover_device.clone()))
+                    .with_sound(args.pushover_sound.clone());
+
+                monitored.push(Wallet {
+                    label: wallet.label,
+                    address: wallet.address.parse()?,
+                    pushover,
+                    gnosispay: wallet.session_token.or(args.session_token.clone()).map(
+                        |session_token| GnosisPay::new(session_token).with_client(client.clone()),
+                    ),
+                    thresholds: BalanceThresholds {
+                        eure: wallet.min_eure_balance.or(args.min_eure_balance),
+                        xdai: wallet.min_xdai_balance.or(args.min_xdai_balance),
+                    },
+                });
+            }
+
+            // Parameters applied to every transfer notification.
+            let template = match args.pushover_priority.unwrap_or(Priority::Normal) {
+                Priority::Emergency => {
+                    Message::default().emergency(args.pushover_retry, args.pushover_expire)
+                }
+                priority => Message::default().priority(priority),
+            };
+
+            let rules = match &args.rules {
+                Some(filename) => Rules::load(filename)?,
+                None => Rules::default(),
+            };
+
+            let metrics = Arc::new(Metrics::default());
+
+            if let Some(listen) = args.metrics_listen {
+                let metrics = metrics.clone();
+                let max_block_age = args.health_max_block_age;
+
+                tokio::spawn(async move {
+                    if let Err(err) = metrics::serve(metrics, listen, max_block_age).await {
+                        eprintln!("{}", format!("[-] Metrics listener: {}", err).red());
+                    }
+                });
+
+                println!(
+                    "{}",
+                    format!("[+] Serving /healthz and /metrics on `{}`", listen).green()
+                );
+            }
+
+            let mqtt = mqtt(&args)?;
+            let options = MonitorOptions {
+                rpc_url: args
+                    .rpc_url
+                    .unwrap_or_else(|| String::from(OnchainArgs::RPC_URL)),
+                token: args.token.unwrap_or_default(),
+                template,
+                rules,
+                balance_interval: Duration::from_secs(args.balance_interval),
+                budgets: budgets(&profile)?,
+                categories: profile.categories.clone(),
+                subscription_alerts: args.subscription_alerts,
+                mqtt,
+                address_book: profile.addresses.clone(),
+            };
+
+            monitor(monitored, options, metrics).await?
+        }
+
+        args::CmdSubcommand::Login(args) => login(args, client).await?,
+
+        args::CmdSubcommand::Secrets(args) => manage_secrets(args)?,
+
+        args::CmdSubcommand::Report(mut args) => {
+            args.list.merge(&profile);
+            let filter = args.filter.filter()?;
+            let mut transactions =
+                load_transactions(args.list, &client, recording.clone(), &profile, &filter).await?;
+            transactions.retain(&filter);
+            let report = Report::new(&transactions, args.by);
+
+            match (args.format, args.output) {
+                (ReportFormat::Table, _) => println!("{}", report),
+                (ReportFormat::Csv, Some(filename)) => {
+                    let delimiter = profile
+                        .export
+                        .delimiter
+                        .unwrap_or(CsvOptions::default().delimiter);
+                    report.to_csv(filename, delimiter)?
+                }
+                (ReportFormat::Csv, None) => {
+                    return Err("a CSV report needs `--output <filename>`".into());
+                }
+                (ReportFormat::Json, Some(filename)) => {
+                    std::fs::write(&filename, report.to_json()?)?;
+                    println!("{}", format!("[+] JSON export to `{}`.", filename).green());
+                }
+                (ReportFormat::Json, None) => println!("{}", report.to_json()?),
+            }
+        }
+
+        args::CmdSubcommand::Summary(mut args) => {
+            args.merge(&profile);
+            let start = args.fiscal_year_start.unwrap_or_default();
+            let year = args
+                .year
+                .unwrap_or_else(|| start.year_of(Local::now().date_naive()));
             let transactions = load_transactions(
-                args.list.wallet_address,
-                args.list.session_token,
-                args.list.gnosisscan_api_key,
+                args.list,
+                &client,
+                recording.clone(),
+                &profile,
+                &Filter::default(),
             )
             .await?;
+            let summary = Summary::new(&transactions, year, start)?;
 
-            transactions.to_csv(args.filename)?;
+            match (args.format, args.output) {
+                (ReportFormat::Table, _) => print!("{}", summary),
+                (ReportFormat::Csv, Some(filename)) => {
+                    let delimiter = profile
+                        .export
+                        .delimiter
+                        .unwrap_or(CsvOptions::default().delimiter);
+                    summary.to_csv(filename, delimiter)?
+                }
+                (ReportFormat::Csv, None) => {
+                    return Err("a CSV summary needs `--output <filename>`".into());
+                }
+                (ReportFormat::Json, Some(filename)) => {
+                    std::fs::write(&filename, summary.to_json()?)?;
+                    println!("{}", format!("[+] JSON export to `{}`.", filename).green());
+                }
+                (ReportFormat::Json, None) => println!("{}", summary.to_json()?),
+            }
         }
 
-        args::CmdSubcommand::Verify(args) => {
+        args::CmdSubcommand::Budget(mut args) => {
+            args.merge(&profile);
+            let budgets = budgets(&profile)?;
+            if budgets.is_empty() {
+                return Err("no budget in the profile, add `[[profile.<name>.budgets]]`".into());
+            }
+
             let transactions = load_transactions(
-                args.wallet_address,
-                args.session_token,
-                args.gnosisscan_api_key,
+                args,
+                &client,
+                recording.clone(),
+                &profile,
+                &Filter::default(),
             )
             .await?;
-            transactions.verify();
+            show_budgets(&budgets, &transactions);
+        }
+
+        args::CmdSubcommand::Subscriptions(mut args) => {
+            args.merge(&profile);
+            let transactions = load_transactions(
+                args,
+                &client,
+                recording.clone(),
+                &profile,
+                &Filter::default(),
+            )
+            .await?;
+            show_subscriptions(&transactions);
+        }
+
+        args::CmdSubcommand::Tui(mut args) => {
+            args.list.merge(&profile);
+            let filter = args.filter.filter()?;
+            let mut transactions =
+                load_transactions(args.list, &client, recording.clone(), &profile, &filter).await?;
+            transactions.retain(&filter);
+            tui::run(transactions, Annotations::open()?)?
         }
 
-        args::CmdSubcommand::Monitor(args) => {
-            monitor(
-                args.wallet_address,
-                args.rpc_url,
-                args.pushover_user,
-                args.pushover_token,
+        args::CmdSubcommand::Annotate(args) => annotate(args)?,
+
+        args::CmdSubcommand::Split(mut args) => {
+            args.list.merge(&profile);
+            let transactions = load_transactions(
+                args.list,
+                &client,
+                recording.clone(),
+                &profile,
+                &Filter::default(),
             )
-            .await?
+            .await?;
+            split(&args.hash, args.parts, args.clear, &transactions)?
+        }
+
+        args::CmdSubcommand::MissingReceipts(mut args) => {
+            args.list.merge(&profile);
+            let filter = args.filter.filter()?;
+            let mut transactions =
+                load_transactions(args.list, &client, recording.clone(), &profile, &filter).await?;
+            transactions.retain(&filter);
+            show_missing_receipts(&transactions);
+        }
+
+        args::CmdSubcommand::Serve(mut args) => {
+            args.list.merge(&profile);
+
+            // Fail early on bad credentials, then keep the last good load.
+            let store = Arc::new(Store::default());
+            store.update(
+                load_transactions(
+                    args.list.clone(),
+                    &client,
+                    recording.clone(),
+                    &profile,
+                    &Filter::default(),
+                )
+                .await?,
+            );
+
+            let refresh = {
+                let store = store.clone();
+                let profile = profile.clone();
+                let mut interval =
+                    tokio::time::interval(Duration::from_secs(args.refresh_interval));
+                interval.tick().await;
+
+                async move {
+                    loop {
+                        interval.tick().await;
+                        match load_transactions(
+                            args.list.clone(),
+                            &client,
+                            recording.clone(),
+                            &profile,
+                            &Filter::default(),
+                        )
+                        .await
+                        {
+                            Ok(transactions) => store.update(transactions),
+                            Err(err) => eprintln!("{}", format!("[-] Refresh: {}", err).red()),
+                        }
+                    }
+                }
+            };
+            tokio::spawn(refresh);
+
+            println!(
+                "{}",
+                format!("[+] Serving the API on `{}`", args.listen).green()
+            );
+            api::serve(store, args.listen, args.api_token).await?
+        }
+
+        args::CmdSubcommand::Limits(mut args) => {
+            args.merge(&profile);
+            limits(args).await?
+        }
+
+        args::CmdSubcommand::Safe(mut args) => {
+            args.merge(&profile);
+            safe(args, &profile.addresses).await?
+        }
+
+        args::CmdSubcommand::Config(args) => {
+            show_config(args, &config_path, &config, profile_name, profile)?
         }
     }
 
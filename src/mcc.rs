@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Friendly groups of merchant categories.
+pub const GROCERIES: &str = "Groceries";
+pub const RESTAURANTS: &str = "Restaurants";
+pub const TRANSPORT: &str = "Transport";
+pub const TRAVEL: &str = "Travel";
+pub const SHOPPING: &str = "Shopping";
+pub const ENTERTAINMENT: &str = "Entertainment";
+pub const HEALTH: &str = "Health";
+pub const UTILITIES: &str = "Utilities";
+pub const SERVICES: &str = "Services";
+pub const CASH: &str = "Cash";
+pub const EDUCATION: &str = "Education";
+pub const GOVERNMENT: &str = "Government";
+pub const OTHER: &str = "Other";
+
+/// ISO 18245 merchant category codes, sorted by code.
+#[rustfmt::skip]
+const CODES: &[(u16, &str, &str)] = &[
+    (4111, "Local and Suburban Commuter Passenger Transportation", TRANSPORT),
+    (4112, "Passenger Railways", TRANSPORT),
+    (4121, "Taxicabs and Limousines", TRANSPORT),
+    (4131, "Bus Lines", TRANSPORT),
+    (4214, "Motor Freight Carriers and Trucking", SERVICES),
+    (4215, "Courier Services", SERVICES),
+    (4411, "Steamship and Cruise Lines", TRAVEL),
+    (4511, "Airlines and Air Carriers", TRAVEL),
+    (4722, "Travel Agencies and Tour Operators", TRAVEL),
+    (4784, "Tolls and Bridge Fees", TRANSPORT),
+    (4789, "Transportation Services", TRANSPORT),
+    (4812, "Telecommunication Equipment and Telephone Sales", SHOPPING),
+    (4814, "Telecommunication Services", UTILITIES),
+    (4816, "Computer Network and Information Services", UTILITIES),
+    (4899, "Cable, Satellite and Other Pay Television and Radio Services", UTILITIES),
+    (4900, "Utilities: Electric, Gas, Water and Sanitary", UTILITIES),
+    (5200, "Home Supply Warehouse Stores", SHOPPING),
+    (5211, "Lumber and Building Materials Stores", SHOPPING),
+    (5251, "Hardware Stores", SHOPPING),
+    (5261, "Nurseries and Lawn and Garden Supply Stores", SHOPPING),
+    (5300, "Wholesale Clubs", GROCERIES),
+    (5310, "Discount Stores", SHOPPING),
+    (5311, "Department Stores", SHOPPING),
+    (5331, "Variety Stores", SHOPPING),
+    (5399, "Miscellaneous General Merchandise", SHOPPING),
+    (5411, "Grocery Stores and Supermarkets", GROCERIES),
+    (5422, "Freezer and Locker Meat Provisioners", GROCERIES),
+    (5441, "Candy, Nut and Confectionery Stores", GROCERIES),
+    (5451, "Dairy Products Stores", GROCERIES),
+    (5462, "Bakeries", GROCERIES),
+    (5499, "Miscellaneous Food Stores", GROCERIES),
+    (5511, "Car and Truck Dealers", TRANSPORT),
+    (5533, "Automotive Parts and Accessories Stores", TRANSPORT),
+    (5541, "Service Stations", TRANSPORT),
+    (5542, "Automated Fuel Dispensers", TRANSPORT),
+    (5611, "Men's and Boys' Clothing and Accessories Stores", SHOPPING),
+    (5621, "Women's Ready-to-Wear Stores", SHOPPING),
+    (5641, "Children's and Infants' Wear Stores", SHOPPING),
+    (5651, "Family Clothing Stores", SHOPPING),
+    (5655, "Sports and Riding Apparel Stores", SHOPPING),
+    (5661, "Shoe Stores", SHOPPING),
+    (5691, "Men's and Women's Clothing Stores", SHOPPING),
+    (5699, "Miscellaneous Apparel and Accessory Stores", SHOPPING),
+    (5712, "Furniture, Home Furnishings and Equipment Stores", SHOPPING),
+    (5722, "Household Appliance Stores", SHOPPING),
+    (5732, "Electronics Stores", SHOPPING),
+    (5734, "Computer Software Stores", SHOPPING),
+    (5735, "Record Stores", ENTERTAINMENT),
+    (5811, "Caterers", RESTAURANTS),
+    (5812, "Eating Places and Restaurants", RESTAURANTS),
+    (5813, "Drinking Places (Alcoholic Beverages)", RESTAURANTS),
+    (5814, "Fast Food Restaurants", RESTAURANTS),
+    (5815, "Digital Goods: Media, Books, Movies, Music", ENTERTAINMENT),
+    (5816, "Digital Goods: Games", ENTERTAINMENT),
+    (5817, "Digital Goods: Applications", ENTERTAINMENT),
+    (5818, "Digital Goods: Large Digital Goods Merchant", ENTERTAINMENT),
+    (5912, "Drug Stores and Pharmacies", HEALTH),
+    (5921, "Package Stores (Beer, Wine and Liquor)", GROCERIES),
+    (5941, "Sporting Goods Stores", SHOPPING),
+    (5942, "Book Stores", SHOPPING),
+    (5943, "Stationery, Office and School Supply Stores", SHOPPING),
+    (5945, "Hobby, Toy and Game Shops", SHOPPING),
+    (5977, "Cosmetic Stores", SHOPPING),
+    (5992, "Florists", SHOPPING),
+    (5993, "Cigar Stores and Stands", SHOPPING),
+    (5994, "News Dealers and Newsstands", SHOPPING),
+    (5995, "Pet Shops, Pet Food and Supplies", SHOPPING),
+    (5999, "Miscellaneous and Specialty Retail Stores", SHOPPING),
+    (6010, "Financial Institutions: Manual Cash Disbursements", CASH),
+    (6011, "Financial Institutions: Automated Cash Disbursements", CASH),
+    (6051, "Non-Financial Institutions: Foreign Currency, Money Orders", CASH),
+    (6300, "Insurance Sales, Underwriting and Premiums", SERVICES),
+    (7011, "Lodging: Hotels, Motels, Resorts", TRAVEL),
+    (7210, "Laundry, Cleaning and Garment Services", SERVICES),
+    (7230, "Beauty and Barber Shops", SERVICES),
+    (7298, "Health and Beauty Spas", HEALTH),
+    (7311, "Advertising Services", SERVICES),
+    (7372, "Computer Programming, Data Processing", SERVICES),
+    (7399, "Business Services", SERVICES),
+    (7512, "Car Rental Agencies", TRANSPORT),
+    (7523, "Parking Lots and Garages", TRANSPORT),
+    (7538, "Automotive Service Shops", TRANSPORT),
+    (7542, "Car Washes", TRANSPORT),
+    (7832, "Motion Picture Theaters", ENTERTAINMENT),
+    (7841, "Video Tape Rental Stores", ENTERTAINMENT),
+    (7922, "Theatrical Producers and Ticket Agencies", ENTERTAINMENT),
+    (7929, "Bands, Orchestras and Miscellaneous Entertainers", ENTERTAINMENT),
+    (7941, "Commercial Sports, Professional Sports Clubs", ENTERTAINMENT),
+    (7991, "Tourist Attractions and Exhibits", ENTERTAINMENT),
+    (7994, "Video Game Arcades", ENTERTAINMENT),
+    (7996, "Amusement Parks, Circuses, Carnivals", ENTERTAINMENT),
+    (7997, "Membership Clubs (Sports, Recreation, Athletic)", ENTERTAINMENT),
+    (7999, "Recreation Services", ENTERTAINMENT),
+    (8011, "Doctors and Physicians", HEALTH),
+    (8021, "Dentists and Orthodontists", HEALTH),
+    (8042, "Optometrists and Ophthalmologists", HEALTH),
+    (8043, "Opticians, Optical Goods and Eyeglasses", HEALTH),
+    (8062, "Hospitals", HEALTH),
+    (8099, "Medical Services and Health Practitioners", HEALTH),
+    (8211, "Elementary and Secondary Schools", EDUCATION),
+    (8220, "Colleges and Universities", EDUCATION),
+    (8299, "Schools and Educational Services", EDUCATION),
+    (8398, "Charitable and Social Service Organizations", SERVICES),
+    (8999, "Professional Services", SERVICES),
+    (9211, "Court Costs", GOVERNMENT),
+    (9222, "Fines", GOVERNMENT),
+    (9311, "Tax Payments", GOVERNMENT),
+    (9399, "Government Services", GOVERNMENT),
+    (9402, "Postal Services", GOVERNMENT),
+];
+
+fn lookup(mcc: &str) -> Option<&'static (u16, &'static str, &'static str)> {
+    let code = mcc.trim().parse::<u16>().ok()?;
+    CODES
+        .binary_search_by_key(&code, |entry| entry.0)
+        .ok()
+        .map(|index| &CODES[index])
+}
+
+/// ISO 18245 description of a merchant category code.
+pub fn description(mcc: &str) -> Option<&'static str> {
+    if let Some(entry) = lookup(mcc) {
+        return Some(entry.1);
+    }
+
+    match mcc.trim().parse::<u16>().ok()? {
+        3000..=3299 => Some("Airlines"),
+        3300..=3499 => Some("Car Rental Agencies"),
+        3500..=3999 => Some("Lodging: Hotels, Motels, Resorts"),
+        _ => None,
+    }
+}
+
+/// Friendly group of a merchant category code, `Other` when unknown.
+pub fn category(mcc: &str) -> &'static str {
+    if let Some(entry) = lookup(mcc) {
+        return entry.2;
+    }
+
+    match mcc.trim().parse::<u16>() {
+        Ok(3000..=3299) => TRAVEL,
+        Ok(3300..=3499) => TRANSPORT,
+        Ok(3500..=3999) => TRAVEL,
+        _ => OTHER,
+    }
+}
+
+/// User categories, replacing the ones from the MCC table.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Overrides {
+    /// Category per merchant name, case insensitive.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub merchant: BTreeMap<String, String>,
+    /// Category per merchant category code.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mcc: BTreeMap<String, String>,
+}
+
+impl Overrides {
+    /// Category set by the user for a merchant or its category code.
+    pub fn category(&self, merchant: &str, mcc: Option<&str>) -> Option<&String> {
+        let merchant = merchant.trim();
+        self.merchant
+            .iter()
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case(merchant))
+            .map(|(_, category)| category)
+            .or_else(|| mcc.and_then(|mcc| self.mcc.get(mcc.trim())))
+    }
+}
@@ -0,0 +1,236 @@
+use crate::prelude::*;
+use axum::Router;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Counters shared between the monitor and the HTTP listener.
+#[derive(Debug, Default)]
+pub struct Metrics {
+    pub connected: AtomicBool,
+    pub events_seen: AtomicU64,
+    pub notifications_sent: AtomicU64,
+    pub notifications_failed: AtomicU64,
+    pub reconnects: AtomicU64,
+    /// Last block header received from the subscription.
+    pub last_block: AtomicU64,
+    /// Unix timestamp when `last_block` was received.
+    pub last_block_seen_at: AtomicI64,
+    /// Chain head as reported by the RPC endpoint.
+    pub chain_head: AtomicU64,
+    /// Card payments count and amount per wallet label.
+    pub wallet_spent: Mutex<HashMap<String, (u64, f64)>>,
+    /// Last balance per wallet label and asset.
+    pub wallet_balance: Mutex<HashMap<(String, String), f64>>,
+}
+
+impl Metrics {
+    pub fn block_seen(&self, number: u64) {
+        self.last_block.store(number, Ordering::Relaxed);
+        self.last_block_seen_at
+            .store(chrono::Utc::now().timestamp(), Ordering::Relaxed);
+    }
+
+    pub fn notification(&self, success: bool) {
+        if success {
+            self.notifications_sent.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.notifications_failed.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn spent(&self, wallet: &str, amount: f64) {
+        let mut wallet_spent = self.wallet_spent.lock().unwrap();
+        let entry = wallet_spent.entry(wallet.to_string()).or_default();
+        entry.0 += 1;
+        entry.1 += amount;
+    }
+
+    pub fn balance(&self, wallet: &str, asset: &str, balance: f64) {
+        self.wallet_balance
+            .lock()
+            .unwrap()
+            .insert((wallet.to_string(), asset.to_string()), balance);
+    }
+
+    /// Number of blocks the subscription is behind the chain head.
+    pub fn lag(&self) -> u64 {
+        self.chain_head
+            .load(Ordering::Relaxed)
+            .saturating_sub(self.last_block.load(Ordering::Relaxed))
+    }
+
+    /// Connected and a block was received within `max_block_age` seconds.
+    pub fn is_healthy(&self, max_block_age: i64) -> bool {
+        let age = chrono::Utc::now().timestamp() - self.last_block_seen_at.load(Ordering::Relaxed);
+        self.connected.load(Ordering::Relaxed) && age <= max_block_age
+    }
+
+    /// Render all metrics in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+
+        let gauges = [
+            (
+                "gnosispay_monitor_connected",
+                "gauge",
+                "Whether the websocket subscription is up.",
+                self.connected.load(Ordering::Relaxed) as u64,
+            ),
+            (
+                "gnosispay_monitor_events_total",
+                "counter",
+                "Transfer events received.",
+                self.events_seen.load(Ordering::Relaxed),
+            ),
+            (
+                "gnosispay_monitor_notifications_sent_total",
+                "counter",
+                "Notifications successfully sent.",
+                self.notifications_sent.load(Ordering::Relaxed),
+            ),
+            (
+                "gnosispay_monitor_notifications_failed_total",
+                "counter",
+                "Notifications that could not be sent.",
+                self.notifications_failed.load(Ordering::Relaxed),
+            ),
+            (
+                "gnosispay_monitor_reconnects_total",
+                "counter",
+                "Websocket reconnections.",
+                self.reconnects.load(Ordering::Relaxed),
+            ),
+            (
+                "gnosispay_monitor_last_block",
+                "gauge",
+                "Last block received from the subscription.",
+                self.last_block.load(Ordering::Relaxed),
+            ),
+            (
+                "gnosispay_monitor_last_block_timestamp_seconds",
+                "gauge",
+                "When the last block was received.",
+                self.last_block_seen_at.load(Ordering::Relaxed) as u64,
+            ),
+            (
+                "gnosispay_monitor_lag_blocks",
+                "gauge",
+                "Blocks behind the chain head.",
+                self.lag(),
+            ),
+        ];
+
+        for (name, kind, help, value) in gauges {
+            let _ = writeln!(out, "# HELP {} {}", name, help);
+            let _ = writeln!(out, "# TYPE {} {}", name, kind);
+            let _ = writeln!(out, "{} {}", name, value);
+        }
+
+        let wallet_spent = self.wallet_spent.lock().unwrap();
+
+        let _ = writeln!(
+            out,
+            "# HELP gnosispay_wallet_payments_total Card payments per wallet."
+        );
+        let _ = writeln!(out, "# TYPE gnosispay_wallet_payments_total counter");
+        for (wallet, (count, _)) in wallet_spent.iter() {
+            let _ = writeln!(
+                out,
+                "gnosispay_wallet_payments_total{{wallet=\"{}\"}} {}",
+                escape_label(wallet),
+                count
+            );
+        }
+
+        let _ = writeln!(
+            out,
+            "# HELP gnosispay_wallet_spent_total Amount spent by card per wallet."
+        );
+        let _ = writeln!(out, "# TYPE gnosispay_wallet_spent_total counter");
+        for (wallet, (_, amount)) in wallet_spent.iter() {
+            let _ = writeln!(
+                out,
+                "gnosispay_wallet_spent_total{{wallet=\"{}\"}} {:.2}",
+                escape_label(wallet),
+                amount
+            );
+        }
+
+        let wallet_balance = self.wallet_balance.lock().unwrap();
+
+        let _ = writeln!(
+            out,
+            "# HELP gnosispay_wallet_balance Last balance per wallet and asset."
+        );
+        let _ = writeln!(out, "# TYPE gnosispay_wallet_balance gauge");
+        for ((wallet, asset), balance) in wallet_balance.iter() {
+            let _ = writeln!(
+                out,
+                "gnosispay_wallet_balance{{wallet=\"{}\",asset=\"{}\"}} {}",
+                escape_label(wallet),
+                escape_label(asset),
+                balance
+            );
+        }
+
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[derive(Clone)]
+struct ServerState {
+    metrics: Arc<Metrics>,
+    max_block_age: i64,
+}
+
+async fn healthz(State(state): State<ServerState>) -> (StatusCode, &'static str) {
+    if state.metrics.is_healthy(state.max_block_age) {
+        (StatusCode::OK, "ok\n")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "unhealthy\n")
+    }
+}
+
+async fn metrics(State(state): State<ServerState>) -> String {
+    state.metrics.render()
+}
+
+/// Routes of `/healthz` and `/metrics`, unhealthy without a block for
+/// `max_block_age` seconds.
+pub fn router(metrics_state: Arc<Metrics>, max_block_age: i64) -> Router {
+    let state = ServerState {
+        metrics: metrics_state,
+        max_block_age,
+    };
+
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/metrics", get(metrics))
+        .with_state(state)
+}
+
+/// Serve `/healthz` and `/metrics` on the given address.
+pub async fn serve(
+    metrics_state: Arc<Metrics>,
+    listen: SocketAddr,
+    max_block_age: i64,
+) -> Result<()> {
+    let app = router(metrics_state, max_block_age);
+    let listener = tokio::net::TcpListener::bind(listen).await?;
+    axum::serve(listener, app).await?;
+
+    Ok(())
+}
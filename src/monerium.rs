@@ -1,3 +1,45 @@
-pub static EURE_V2_ADDRESS: &str = "0x420CA0f9B9b604cE0fd9C18EF134C705e5Fa3430";
+use alloy::primitives::{Address, address};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+pub static EURE_V2_ADDRESS: Address = address!("0x420CA0f9B9b604cE0fd9C18EF134C705e5Fa3430");
 pub static EURE_V2_DECIMALS: u8 = 18;
+pub static EURE_V1_ADDRESS: Address = address!("0xcB444e90D8198415266c6a2724b7900fb12FC56E");
+pub static EURE_V1_DECIMALS: u8 = 18;
 pub static GNOSIS_BANK: &str = "0x4822521e6135cd2599199c83ea35179229a172ee";
+
+/// Monerium token used by the card.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Token {
+    /// EURe v2.
+    #[default]
+    Eure,
+    /// EURe before the v2 migration, for older history.
+    EureV1,
+}
+
+impl Token {
+    pub fn address(&self) -> Address {
+        match self {
+            Token::Eure => EURE_V2_ADDRESS,
+            Token::EureV1 => EURE_V1_ADDRESS,
+        }
+    }
+
+    pub fn decimals(&self) -> u8 {
+        match self {
+            Token::Eure => EURE_V2_DECIMALS,
+            Token::EureV1 => EURE_V1_DECIMALS,
+        }
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Eure => write!(f, "EURe"),
+            Token::EureV1 => write!(f, "EURe v1"),
+        }
+    }
+}
@@ -1,13 +1,28 @@
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
-use crate::monerium;
-use crate::notify::PushOverNotify;
+use crate::address_book::AddressBook;
+use crate::budget::Budget;
+use crate::gnosispay::{GnosisPay, GnosisTransaction};
+use crate::mcc::Overrides;
+use crate::metrics::Metrics;
+use crate::monerium::{self, Token};
+use crate::mqtt::{Mqtt, TransferMessage};
+use crate::notify::{Message, Priority, PushOverNotify};
 use crate::prelude::*;
-use alloy::primitives::{Address, utils::format_units};
+use crate::rules::{Channel, Payment, Rules};
+use crate::safe::{Allowance, IDelay, QueuedTransaction, SafeInfo};
+use crate::subscriptions::Subscription;
+use crate::transactions::{Kind, Transaction};
+use alloy::primitives::{Address, B256, utils::format_units};
 use alloy::providers::{Provider, ProviderBuilder, WsConnect};
-use alloy::rpc::types::{BlockNumberOrTag, Filter};
+use alloy::rpc::types::{BlockNumberOrTag, Filter, Log};
 use alloy::sol;
 use alloy::sol_types::SolEvent;
+use chrono::{DateTime, Local, NaiveDate};
 use colored::Colorize;
 use futures_util::StreamExt;
 
@@ -25,66 +40,881 @@ sol!(
     }
 );
 
-/// Monitor transaction to Gnosis Pay from wallet_address.
+/// A monitored wallet with its own notification recipient.
+pub struct Wallet {
+    pub label: String,
+    pub address: Address,
+    pub pushover: PushOverNotify,
+    /// Used to enrich notifications with merchant details.
+    pub gnosispay: Option<GnosisPay>,
+    pub thresholds: BalanceThresholds,
+}
+
+/// Balances below which the wallet owner is asked to top up.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BalanceThresholds {
+    pub eure: Option<f64>,
+    pub xdai: Option<f64>,
+}
+
+/// Wallets and assets currently below their threshold, with the last time
+/// the owner was told about it.
+#[derive(Debug, Default)]
+pub struct LowBalances(HashMap<(Address, &'static str), DateTime<Local>>);
+
+impl LowBalances {
+    /// Whether to tell the owner about a balance: when it drops below the
+    /// threshold, then once a day while it stays there. Back above the
+    /// threshold, the next drop is told at once.
+    pub fn alert(
+        &mut self,
+        wallet: Address,
+        asset: &'static str,
+        balance: f64,
+        threshold: f64,
+        now: DateTime<Local>,
+    ) -> bool {
+        let key = (wallet, asset);
+        if balance >= threshold {
+            self.0.remove(&key);
+            return false;
+        }
+
+        if self
+            .0
+            .get(&key)
+            .is_some_and(|last| now - *last < LOW_BALANCE_REMINDER)
+        {
+            return false;
+        }
+        self.0.insert(key, now);
+        true
+    }
+}
+
+/// Monitor state, kept across reconnections.
+struct State {
+    spending: Spending,
+    low_balances: LowBalances,
+}
+
+/// Amount spent today per wallet.
+struct Spending {
+    today: NaiveDate,
+    totals: HashMap<Address, f64>,
+}
+
+impl Spending {
+    /// Add an amount to today's total of a wallet and return the new total.
+    fn add(&mut self, wallet: Address, now: DateTime<Local>, amount: f64) -> f64 {
+        if now.date_naive() != self.today {
+            self.today = now.date_naive();
+            self.totals.clear();
+        }
+        let total = self.totals.entry(wallet).or_default();
+        *total += amount;
+        *total
+    }
+
+    /// Today's total of a wallet.
+    fn total(&self, wallet: Address, now: DateTime<Local>) -> f64 {
+        match now.date_naive() == self.today {
+            true => self.totals.get(&wallet).copied().unwrap_or_default(),
+            false => 0.0,
+        }
+    }
+}
+
+/// Delay before reconnecting after the subscription dropped.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// How often the chain head is polled to compute the lag.
+const CHAIN_HEAD_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How often a low balance notification is repeated.
+const LOW_BALANCE_REMINDER: chrono::Duration = chrono::Duration::hours(24);
+
+/// Monitor settings shared by all wallets.
+pub struct MonitorOptions {
+    pub rpc_url: String,
+    pub token: Token,
+    /// Parameters applied to every transfer notification.
+    pub template: Message,
+    pub rules: Rules,
+    /// Delay between balance checks.
+    pub balance_interval: Duration,
+    /// Budgets checked after each card payment, needs Gnosis Pay.
+    pub budgets: Vec<Budget>,
+    /// User categories of the budgets.
+    pub categories: Overrides,
+    /// Warn when a subscription charges another amount, needs Gnosis Pay.
+    pub subscription_alerts: bool,
+    /// Broker to publish transfers and balances to, for Home Assistant.
+    pub mqtt: Option<Mqtt>,
+    /// Labels of the recipients of transfers and withdrawals.
+    pub address_book: AddressBook,
+}
+
+/// Monitor transactions to Gnosis Pay from all wallets.
 pub async fn monitor(
-    wallet_address: String,
-    rpc_url: String,
-    pushover_user: String,
-    pushover_token: String,
+    wallets: Vec<Wallet>,
+    options: MonitorOptions,
+    metrics: Arc<Metrics>,
 ) -> Result<()> {
-    let pushover = PushOverNotify::new(pushover_user, pushover_token);
-    pushover
-        .notify("GnosisPay".into(), "Starting to monitor".into())
-        .await?;
+    if options.balance_interval.is_zero() {
+        return Err("the balance interval must be at least a second".into());
+    }
 
-    let wallet_address = Address::from_str(&wallet_address)?;
-    let monerium_address = Address::from_str(monerium::GNOSIS_BANK)?;
+    for wallet in &wallets {
+        wallet
+            .pushover
+            .notify(
+                "GnosisPay".into(),
+                format!("Starting to monitor {}", wallet.label),
+            )
+            .await?;
 
-    // Connect to the endpoint.
-    let ws = WsConnect::new(rpc_url.clone());
-    let provider = ProviderBuilder::new().on_ws(ws).await?;
+        if let Some(mqtt) = &options.mqtt {
+            log_mqtt(mqtt.announce(&wallet.label, wallet.address));
+        }
+    }
+
+    let mut state = State {
+        spending: Spending {
+            today: chrono::Local::now().date_naive(),
+            totals: HashMap::new(),
+        },
+        low_balances: LowBalances::default(),
+    };
+
+    // Resubscribe whenever the websocket drops.
+    loop {
+        match watch(&wallets, &options, &metrics, &mut state).await {
+            Ok(()) => eprintln!("{}", "[-] Subscription closed".red()),
+            Err(err) => eprintln!("{}", format!("[-] {}", err).red()),
+        }
 
-    // Subscribe to logs.
+        metrics.connected.store(false, Ordering::Relaxed);
+        metrics.reconnects.fetch_add(1, Ordering::Relaxed);
+        tokio::time::sleep(RECONNECT_DELAY).await;
+
+        println!("{}", "[+] Reconnecting".green());
+    }
+}
+
+/// Filters of the `token` transfers from any of the wallets and to them, two
+/// subscriptions as topics of a filter must all match.
+pub fn transfer_filters(token: Token, wallets: &[Wallet]) -> [Filter; 2] {
+    let addresses: Vec<B256> = wallets
+        .iter()
+        .map(|wallet| wallet.address.into_word())
+        .collect();
     let filter = Filter::new()
-        .address(Address::from_str(monerium::EURE_V2_ADDRESS)?)
+        .address(token.address())
         .event("Transfer(address,address,uint256)")
         .from_block(BlockNumberOrTag::Latest);
 
-    let sub = provider.subscribe_logs(&filter).await?;
-    let mut stream = sub.into_stream();
+    [
+        filter.clone().topic1(addresses.clone()),
+        filter.topic2(addresses),
+    ]
+}
+
+/// Subscribe to transfers and blocks until the connection drops.
+async fn watch(
+    wallets: &[Wallet],
+    options: &MonitorOptions,
+    metrics: &Metrics,
+    state: &mut State,
+) -> Result<()> {
+    // Connect to the endpoint.
+    let ws = WsConnect::new(&options.rpc_url);
+    let provider = ProviderBuilder::new().on_ws(ws).await?;
+
+    let [outgoing, incoming] = transfer_filters(options.token, wallets);
+    let mut logs = provider.subscribe_logs(&outgoing).await?.into_stream();
+    let mut incoming = provider.subscribe_logs(&incoming).await?.into_stream();
+
+    // Withdrawals queued by the owners, or by whoever holds their keys.
+    let mut delay_modules = HashMap::new();
+    // Roles module of each wallet, to tell the allowance left after a payment.
+    let mut roles_modules = HashMap::new();
+    for wallet in wallets {
+        match SafeInfo::load(&provider, wallet.address).await {
+            Ok(info) => {
+                for module in info.delay_modules() {
+                    delay_modules.insert(module, wallet);
+                }
+                if let Some(module) = info.roles_module() {
+                    roles_modules.insert(wallet.address, module);
+                }
+            }
+            Err(err) => eprintln!(
+                "{}",
+                format!("[-] Cannot read the Safe of `{}`: {}", wallet.label, err).red()
+            ),
+        }
+    }
+
+    // Without an address the filter would match every Delay module on chain.
+    let mut queued = None;
+    if !delay_modules.is_empty() {
+        let filter = Filter::new()
+            .address(delay_modules.keys().copied().collect::<Vec<_>>())
+            .event_signature(IDelay::TransactionAdded::SIGNATURE_HASH)
+            .from_block(BlockNumberOrTag::Latest);
+        queued = Some(provider.subscribe_logs(&filter).await?.into_stream());
+    }
+
+    // New blocks tell us the subscription is alive.
+    let mut blocks = provider.subscribe_blocks().await?.into_stream();
+    let mut chain_head = tokio::time::interval(CHAIN_HEAD_INTERVAL);
+    let mut balances = tokio::time::interval(options.balance_interval);
+
+    metrics.connected.store(true, Ordering::Relaxed);
 
     println!(
         "{}",
-        format!("[+] Listening for blocks on `{}`", rpc_url).green()
+        format!(
+            "[+] Listening for {} transfers on `{}` for {} wallet(s), {} Delay module(s)",
+            options.token,
+            options.rpc_url,
+            wallets.len(),
+            delay_modules.len()
+        )
+        .green()
     );
 
     // Check each blocks.
-    while let Some(log) = stream.next().await {
-        match log.topic0() {
-            Some(&IERC20::Transfer::SIGNATURE_HASH) => {
-                let IERC20::Transfer { from, to, value } = log.log_decode()?.inner.data;
+    loop {
+        tokio::select! {
+            log = logs.next() => {
+                let Some(log) = log else { return Ok(()) };
+                metrics.events_seen.fetch_add(1, Ordering::Relaxed);
+                let allowances = Allowances { provider: &provider, modules: &roles_modules };
+                let from = handle_transfer(log, wallets, options, &allowances, metrics, &mut state.spending).await?;
+                if let (Some(mqtt), Some(wallet)) = (&options.mqtt, from) {
+                    publish_balances(&provider, options.token, mqtt, wallet).await;
+                }
+            }
+            log = incoming.next() => {
+                let Some(log) = log else { return Ok(()) };
+                metrics.events_seen.fetch_add(1, Ordering::Relaxed);
+                let to = handle_incoming(log, wallets, options)?;
+                if let (Some(mqtt), Some(wallet)) = (&options.mqtt, to) {
+                    publish_balances(&provider, options.token, mqtt, wallet).await;
+                }
+            }
+            log = async { queued.as_mut().unwrap().next().await }, if queued.is_some() => {
+                let Some(log) = log else { return Ok(()) };
+                metrics.events_seen.fetch_add(1, Ordering::Relaxed);
+                handle_queued(&provider, log, &delay_modules, &options.address_book, metrics).await;
+            }
+            header = blocks.next() => {
+                let Some(header) = header else { return Ok(()) };
+                metrics.block_seen(header.number);
+            }
+            _ = chain_head.tick() => {
+                let number = provider.get_block_number().await?;
+                metrics.chain_head.store(number, Ordering::Relaxed);
+            }
+            _ = balances.tick() => {
+                check_balances(&provider, wallets, options, metrics, &mut state.low_balances).await?;
+                if let Some(mqtt) = &options.mqtt {
+                    let now = chrono::Local::now();
+                    for wallet in wallets {
+                        log_mqtt(mqtt.spent_today(wallet.address, state.spending.total(wallet.address, now), now));
+                    }
+                }
+            }
+        }
+    }
+}
 
-                let amount = format_units(value, monerium::EURE_V2_DECIMALS)?.parse::<f64>()?;
+/// Reads the spending allowance left after a payment.
+struct Allowances<'a, P> {
+    provider: &'a P,
+    /// Roles module per wallet.
+    modules: &'a HashMap<Address, Address>,
+}
 
-                if from == wallet_address && to == monerium_address {
-                    let now = chrono::Local::now();
+impl<P: Provider> Allowances<'_, P> {
+    /// Allowance left today, `None` when it cannot be read.
+    async fn remaining(&self, wallet: Address, token: Token) -> Option<f64> {
+        let module = self.modules.get(&wallet)?;
+        let allowance = match Allowance::load(self.provider, *module).await {
+            Ok(allowance) => allowance,
+            Err(err) => {
+                eprintln!(
+                    "{}",
+                    format!("[-] Cannot read the allowance: {}", err).red()
+                );
+                return None;
+            }
+        };
 
-                    println!(
-                        "[+] {}: Transfer from `{}` to `{}` of value {:.2}",
-                        now.format("%Y-%m-%d %H:%M:%S"),
-                        from,
-                        to,
-                        amount
-                    );
-
-                    pushover
-                        .notify(String::from("GnosisPay"), format!("Amount: {:.2}", amount))
-                        .await?;
-                }
+        let remaining = allowance.remaining(chrono::Utc::now());
+        format_units(remaining, token.decimals()).ok()?.parse().ok()
+    }
+}
+
+/// Notify the owner of a wallet about a card payment or an on-chain send,
+/// and publish any transfer to MQTT. Returns the wallet the transfer was
+/// sent from.
+async fn handle_transfer<P: Provider>(
+    log: Log,
+    wallets: &[Wallet],
+    options: &MonitorOptions,
+    allowances: &Allowances<'_, P>,
+    metrics: &Metrics,
+    spending: &mut Spending,
+) -> Result<Option<Address>> {
+    let monerium_address = Address::from_str(monerium::GNOSIS_BANK)?;
+
+    if let Some(&IERC20::Transfer::SIGNATURE_HASH) = log.topic0() {
+        let IERC20::Transfer { from, to, value } = log.log_decode()?.inner.data;
+
+        let amount = format_units(value, options.token.decimals())?.parse::<f64>()?;
+
+        let Some(wallet) = wallets.iter().find(|wallet| wallet.address == from) else {
+            return Ok(None);
+        };
+
+        let now = chrono::Local::now();
+        let mut transfer = TransferMessage {
+            kind: match to {
+                to if to == monerium_address => Kind::Card,
+                Address::ZERO => Kind::Burned,
+                _ => Kind::Sent,
+            },
+            hash: log.transaction_hash.map(|hash| hash.to_string()),
+            wallet: from,
+            from,
+            to,
+            sender: Some(wallet.label.clone()),
+            recipient: options.address_book.label(to).map(String::from),
+            amount,
+            time: now,
+            merchant: None,
+            category: None,
+        };
+
+        println!(
+            "[+] {}: Transfer from `{}` ({}) to {} of value {:.2}",
+            now.format("%Y-%m-%d %H:%M:%S"),
+            from,
+            wallet.label,
+            options.address_book.describe(to),
+            amount
+        );
+
+        if transfer.kind != Kind::Card {
+            if let Some(mqtt) = &options.mqtt {
+                log_mqtt(mqtt.transfer(&transfer));
+            }
+
+            let mut message = Message {
+                title: format!("GnosisPay - {}", wallet.label),
+                message: format!(
+                    "Amount: {:.2}\nTo: {}",
+                    amount,
+                    options.address_book.describe(to)
+                ),
+                ..options.template.clone()
+            };
+            if let Some(hash) = log.transaction_hash {
+                message = message.url(
+                    format!("https://gnosisscan.io/tx/{}", hash),
+                    Some(String::from("View on Gnosisscan")),
+                );
+            }
+
+            let result = wallet.pushover.send(&message).await;
+            metrics.notification(result.is_ok());
+            if let Err(err) = result {
+                eprintln!("{}", format!("[-] {}", err).red());
+            }
+            return Ok(Some(from));
+        }
+
+        let daily_total = spending.add(from, now, amount);
+        metrics.spent(&wallet.label, amount);
+
+        // Look for the card payment details on Gnosis Pay.
+        let payments = match (&wallet.gnosispay, log.transaction_hash) {
+            (Some(gnosispay), Some(_)) => retrieve_gnosispay_transactions(gnosispay).await,
+            _ => Vec::new(),
+        };
+        let details = log.transaction_hash.and_then(|hash| {
+            let hash = hash.to_string();
+            payments
+                .iter()
+                .find(|payment| {
+                    payment
+                        .transactions
+                        .iter()
+                        .any(|tx| tx.hash.eq_ignore_ascii_case(&hash))
+                })
+                .cloned()
+        });
+
+        let mut text = match &details {
+            Some(details) => format!(
+                "Amount: {:.2}\nMerchant: {}",
+                amount,
+                details.merchant.name.trim()
+            ),
+            None => format!(
+                "Amount: {:.2}\nTo: {}",
+                amount,
+                options.address_book.describe(to)
+            ),
+        };
+
+        if let Some(remaining) = allowances.remaining(from, options.token).await {
+            text.push_str(&format!("\nRemaining today: {:.2}", remaining));
+        }
+
+        let mut message = Message {
+            title: format!("GnosisPay - {}", wallet.label),
+            message: text,
+            ..options.template.clone()
+        };
+
+        if let Some(hash) = log.transaction_hash {
+            message = message.url(
+                format!("https://gnosisscan.io/tx/{}", hash),
+                Some(String::from("View on Gnosisscan")),
+            );
+        }
+
+        let payment = Payment {
+            amount,
+            time: now,
+            daily_total,
+            details: details.clone(),
+        };
+
+        let (name, action) = options.rules.evaluate(&payment);
+        if let Some(name) = name {
+            println!("[+] Matched rule `{}`", name);
+        }
+        message = action.apply(message);
+        let channel = action.channel;
+
+        if channel == Channel::Pushover {
+            let result = wallet.pushover.send(&message).await;
+            metrics.notification(result.is_ok());
+
+            if let Err(err) = result {
+                eprintln!("{}", format!("[-] {}", err).red());
+            }
+        }
+
+        // Card payments known to Gnosis Pay, including this one.
+        let history: Vec<Transaction> = payments
+            .iter()
+            .filter_map(|payment| card_payment(payment, &options.categories))
+            .collect();
+        let payment = details
+            .as_ref()
+            .and_then(|details| card_payment(details, &options.categories));
+
+        if let Some(mqtt) = &options.mqtt {
+            transfer.merchant = details
+                .as_ref()
+                .map(|details| details.merchant.name.trim().to_string());
+            transfer.category = payment
+                .as_ref()
+                .and_then(|payment| payment.category.clone());
+            log_mqtt(mqtt.transfer(&transfer));
+            log_mqtt(mqtt.spent_today(from, daily_total, now));
+        }
+
+        if let Some(payment) = &payment {
+            check_budgets(wallet, options, &history, payment, metrics).await;
+            if options.subscription_alerts {
+                check_subscription(wallet, &history, payment, metrics).await;
+            }
+        }
+
+        return Ok(Some(from));
+    }
+
+    Ok(None)
+}
+
+/// Publish a transfer to a wallet to MQTT: a mint, a top up or a refund.
+/// Returns the wallet the transfer was sent to.
+fn handle_incoming(
+    log: Log,
+    wallets: &[Wallet],
+    options: &MonitorOptions,
+) -> Result<Option<Address>> {
+    if let Some(&IERC20::Transfer::SIGNATURE_HASH) = log.topic0() {
+        let IERC20::Transfer { from, to, value } = log.log_decode()?.inner.data;
+
+        let amount = format_units(value, options.token.decimals())?.parse::<f64>()?;
+
+        let Some(wallet) = wallets.iter().find(|wallet| wallet.address == to) else {
+            return Ok(None);
+        };
+
+        let now = chrono::Local::now();
+        let transfer = TransferMessage {
+            kind: match from {
+                Address::ZERO => Kind::Minted,
+                _ => Kind::Deposit,
+            },
+            hash: log.transaction_hash.map(|hash| hash.to_string()),
+            wallet: to,
+            from,
+            to,
+            sender: options.address_book.label(from).map(String::from),
+            recipient: Some(wallet.label.clone()),
+            amount,
+            time: now,
+            merchant: None,
+            category: None,
+        };
+
+        println!(
+            "[+] {}: Transfer from {} to `{}` ({}) of value {:.2}",
+            now.format("%Y-%m-%d %H:%M:%S"),
+            options.address_book.describe(from),
+            to,
+            wallet.label,
+            amount
+        );
+
+        if let Some(mqtt) = &options.mqtt {
+            log_mqtt(mqtt.transfer(&transfer));
+        }
+        return Ok(Some(to));
+    }
+
+    Ok(None)
+}
+
+/// Gnosis Pay payment with its category, `None` when not settled.
+fn card_payment(payment: &GnosisTransaction, categories: &Overrides) -> Option<Transaction> {
+    let mut transaction = Transaction::from_gnosispay(payment).ok()??;
+    transaction.categorize(categories);
+    Some(transaction)
+}
+
+/// Warn when a card payment pushes a budget over 80% or 100%.
+async fn check_budgets(
+    wallet: &Wallet,
+    options: &MonitorOptions,
+    history: &[Transaction],
+    payment: &Transaction,
+    metrics: &Metrics,
+) {
+    let now = chrono::Local::now();
+    for budget in &options.budgets {
+        if !budget.matches(payment) {
+            continue;
+        }
+
+        // Spending before this payment, which is in the history.
+        let spent = budget.spent(history, &now);
+        let Some(threshold) = budget.crossed(spent - budget.spent_on(payment), spent) else {
+            continue;
+        };
+
+        println!(
+            "{}",
+            format!(
+                "[-] {}: Budget `{}` of `{}` at {:.0}%",
+                now.format("%Y-%m-%d %H:%M:%S"),
+                budget.name(),
+                wallet.label,
+                spent / budget.amount * 100.0
+            )
+            .yellow()
+        );
+
+        let (text, priority) = if threshold >= 1.0 {
+            ("over budget", Priority::High)
+        } else {
+            ("close to the budget", Priority::Normal)
+        };
+        let text = format!(
+            "{} is {}: {:.2} of {:.2} spent this {}.",
+            budget.name(),
+            text,
+            spent,
+            budget.amount,
+            budget.period
+        );
+
+        let message =
+            Message::new(format!("GnosisPay - {}", wallet.label), text).priority(priority);
+        let result = wallet.pushover.send(&message).await;
+        metrics.notification(result.is_ok());
+
+        if let Err(err) = result {
+            eprintln!("{}", format!("[-] {}", err).red());
+        }
+    }
+}
+
+/// Warn when a subscription charges another amount than usual.
+async fn check_subscription(
+    wallet: &Wallet,
+    history: &[Transaction],
+    payment: &Transaction,
+    metrics: &Metrics,
+) {
+    // The usual amount is the one before this charge.
+    let previous = history
+        .iter()
+        .filter(|transaction| transaction.hash != payment.hash);
+    let subscriptions = Subscription::detect(previous);
+    let Some(subscription) = Subscription::find(&subscriptions, payment) else {
+        return;
+    };
+    if !subscription.differs(-payment.amount) {
+        return;
+    }
+
+    println!(
+        "{}",
+        format!(
+            "[-] Subscription `{}` of `{}` charged {:.2} instead of {:.2}",
+            subscription.merchant, wallet.label, -payment.amount, subscription.usual_amount
+        )
+        .yellow()
+    );
+
+    let text = format!(
+        "{} charged {:.2} instead of the usual {:.2}.",
+        subscription.merchant, -payment.amount, subscription.usual_amount
+    );
+    let message = Message::new(format!("GnosisPay - {}", wallet.label), text);
+    let result = wallet.pushover.send(&message).await;
+    metrics.notification(result.is_ok());
+
+    if let Err(err) = result {
+        eprintln!("{}", format!("[-] {}", err).red());
+    }
+}
+
+/// Alert the owner of a wallet as soon as a withdrawal is queued.
+async fn handle_queued<P: Provider>(
+    provider: &P,
+    log: Log,
+    delay_modules: &HashMap<Address, &Wallet>,
+    address_book: &AddressBook,
+    metrics: &Metrics,
+) {
+    let Some(wallet) = delay_modules.get(&log.address()) else {
+        return;
+    };
+    let now = chrono::Utc::now();
+
+    // The alert is sent even when the details cannot be read, the owner
+    // must be able to cancel the withdrawal in time.
+    let event = match log.log_decode::<IDelay::TransactionAdded>() {
+        Ok(decoded) => Some(decoded.inner.data),
+        Err(err) => {
+            eprintln!(
+                "{}",
+                format!("[-] Cannot decode queued transaction: {}", err).red()
+            );
+            None
+        }
+    };
+    let settings = match delay_settings(provider, log.address()).await {
+        Ok(settings) => Some(settings),
+        Err(err) => {
+            eprintln!(
+                "{}",
+                format!("[-] Cannot read the Delay module: {}", err).red()
+            );
+            None
+        }
+    };
+
+    let (summary, text) = match (event, settings) {
+        (Some(event), Some((cooldown, expiration))) => {
+            let transaction = QueuedTransaction::new(event, now, cooldown, expiration);
+            let summary = transaction.summary(address_book);
+            let text = format!(
+                "Withdrawal queued: {}\nExecutable at {}, {} min left to cancel it.",
+                summary,
+                transaction
+                    .executable_at
+                    .with_timezone(&Local)
+                    .format("%H:%M"),
+                (transaction.executable_at - now).num_minutes()
+            );
+            (summary, text)
+        }
+        (Some(event), None) => {
+            let summary = QueuedTransaction::new(event, now, 0, 0).summary(address_book);
+            let text = format!(
+                "Withdrawal queued: {}\nCooldown unknown, check it with `safe queue` now.",
+                summary
+            );
+            (summary, text)
+        }
+        (None, _) => {
+            let summary = format!("unknown transaction on Delay module {}", log.address());
+            let text = format!(
+                "Withdrawal queued: {}\nCheck it with `safe queue` now.",
+                summary
+            );
+            (summary, text)
+        }
+    };
+
+    println!(
+        "{}",
+        format!(
+            "[-] {}: Withdrawal queued on `{}`: {}",
+            now.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            wallet.label,
+            summary
+        )
+        .red()
+    );
+
+    let mut message =
+        Message::new(format!("GnosisPay - {}", wallet.label), text).priority(Priority::High);
+    if let Some(hash) = log.transaction_hash {
+        message = message.url(
+            format!("https://gnosisscan.io/tx/{}", hash),
+            Some(String::from("View on Gnosisscan")),
+        );
+    }
+
+    let result = wallet.pushover.send(&message).await;
+    metrics.notification(result.is_ok());
+
+    if let Err(err) = result {
+        eprintln!("{}", format!("[-] {}", err).red());
+    }
+}
+
+/// Cooldown and expiration of a Delay module, in seconds.
+async fn delay_settings<P: Provider>(provider: &P, module: Address) -> Result<(u64, u64)> {
+    let delay = IDelay::new(module, provider);
+    let cooldown = delay.txCooldown().call().await?._0.try_into()?;
+    let expiration = delay.txExpiration().call().await?._0.try_into()?;
+    Ok((cooldown, expiration))
+}
+
+/// EURe and xDAI balances of a wallet.
+async fn read_balances<P: Provider>(
+    provider: &P,
+    token: Token,
+    wallet: Address,
+) -> Result<(f64, f64)> {
+    let eure = IERC20::new(token.address(), provider);
+    let value = eure.balanceOf(wallet).call().await?._0;
+    let eure_balance = format_units(value, token.decimals())?.parse::<f64>()?;
+
+    let value = provider.get_balance(wallet).await?;
+    let xdai_balance = format_units(value, "ether")?.parse::<f64>()?;
+
+    Ok((eure_balance, xdai_balance))
+}
+
+/// Publish the balances of a wallet after a transfer, Home Assistant would
+/// otherwise wait for the next balance check.
+async fn publish_balances<P: Provider>(provider: &P, token: Token, mqtt: &Mqtt, wallet: Address) {
+    match read_balances(provider, token, wallet).await {
+        Ok((eure, xdai)) => log_mqtt(mqtt.balances(wallet, eure, xdai)),
+        Err(err) => eprintln!("{}", format!("[-] Cannot read the balances: {}", err).red()),
+    }
+}
+
+/// Log MQTT errors, the monitor goes on without the broker.
+fn log_mqtt(result: Result<()>) {
+    if let Err(err) = result {
+        eprintln!("{}", format!("[-] {}", err).red());
+    }
+}
+
+/// Check EURe and xDAI balances and ask to top up when they are low.
+async fn check_balances<P: Provider>(
+    provider: &P,
+    wallets: &[Wallet],
+    options: &MonitorOptions,
+    metrics: &Metrics,
+    low_balances: &mut LowBalances,
+) -> Result<()> {
+    for wallet in wallets {
+        let (eure_balance, xdai_balance) =
+            read_balances(provider, options.token, wallet.address).await?;
+
+        metrics.balance(&wallet.label, "EURe", eure_balance);
+        metrics.balance(&wallet.label, "xDAI", xdai_balance);
+        if let Some(mqtt) = &options.mqtt {
+            log_mqtt(mqtt.balances(wallet.address, eure_balance, xdai_balance));
+        }
+
+        let checks = [
+            ("EURe", eure_balance, wallet.thresholds.eure),
+            ("xDAI", xdai_balance, wallet.thresholds.xdai),
+        ];
+
+        for (asset, balance, threshold) in checks {
+            let Some(threshold) = threshold else {
+                continue;
+            };
+            let now = chrono::Local::now();
+            if !low_balances.alert(wallet.address, asset, balance, threshold, now) {
+                continue;
+            }
+
+            println!(
+                "{}",
+                format!(
+                    "[-] {}: {} balance of `{}` is {:.2}, below {:.2}",
+                    now.format("%Y-%m-%d %H:%M:%S"),
+                    asset,
+                    wallet.label,
+                    balance,
+                    threshold
+                )
+                .yellow()
+            );
+
+            let text = match asset {
+                "EURe" => format!(
+                    "Card balance is {:.2} EURe, below {:.2}. Top up before a payment is declined.",
+                    balance, threshold
+                ),
+                _ => format!(
+                    "Gas balance is {:.4} xDAI, below {:.4}. Top up to keep transacting.",
+                    balance, threshold
+                ),
+            };
+
+            let message = Message::new(format!("GnosisPay - {}", wallet.label), text)
+                .priority(Priority::High);
+            let result = wallet.pushover.send(&message).await;
+            metrics.notification(result.is_ok());
+
+            if let Err(err) = result {
+                eprintln!("{}", format!("[-] {}", err).red());
             }
-            _ => (),
         }
     }
 
     Ok(())
 }
+
+/// Card payments of a wallet on Gnosis Pay, empty when they cannot be read.
+async fn retrieve_gnosispay_transactions(gnosispay: &GnosisPay) -> Vec<GnosisTransaction> {
+    match gnosispay.retrieve_transactions().await {
+        Ok(transactions) => transactions,
+        Err(err) => {
+            eprintln!("{}", format!("[-] Gnosis Pay: {}", err).red());
+            Vec::new()
+        }
+    }
+}
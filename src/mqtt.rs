@@ -0,0 +1,273 @@
+use crate::prelude::*;
+use crate::transactions::Kind;
+use alloy::primitives::Address;
+use chrono::{DateTime, Local, NaiveTime};
+use colored::Colorize;
+use reqwest::Url;
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS, Transport};
+use serde::Serialize;
+use serde_json::{Value, json};
+use std::time::Duration;
+
+/// Topics of the wallets are under `<prefix>/<address>/`.
+pub const TOPIC_PREFIX: &str = "gnosispay";
+
+/// Where Home Assistant looks for discovery payloads, unless changed there.
+pub const DISCOVERY_PREFIX: &str = "homeassistant";
+
+/// Messages waiting to be sent, publishing fails beyond that.
+const CAPACITY: usize = 100;
+
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+
+/// Delay before reconnecting after the broker dropped the connection.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// A transfer seen by the monitor, published to `<prefix>/<address>/transfer`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferMessage {
+    /// `card`, `burn` or `sent` from the wallet, `mint` or `deposit` to it.
+    pub kind: Kind,
+    pub hash: Option<String>,
+    /// Wallet the transfer was seen on, its sender or its recipient.
+    pub wallet: Address,
+    pub from: Address,
+    pub to: Address,
+    /// Label of `from`, from the wallets or the address book.
+    pub sender: Option<String>,
+    /// Label of `to`, from the wallets or the address book.
+    pub recipient: Option<String>,
+    pub amount: f64,
+    pub time: DateTime<Local>,
+    /// Card payments only, when Gnosis Pay knows them.
+    pub merchant: Option<String>,
+    pub category: Option<String>,
+}
+
+/// State of the `spent_today` sensor, with the midnight it was reset at.
+pub fn spent_today_state(amount: f64, now: DateTime<Local>) -> Value {
+    // Midnight may not exist on a daylight saving time change.
+    let last_reset = now
+        .date_naive()
+        .and_time(NaiveTime::MIN)
+        .and_local_timezone(Local)
+        .earliest()
+        .unwrap_or(now);
+    json!({
+        "amount": (amount * 100.0).round() / 100.0,
+        "last_reset": last_reset.to_rfc3339(),
+    })
+}
+
+/// Publishes transfers and balances, with Home Assistant discovery.
+pub struct Mqtt {
+    client: AsyncClient,
+    /// Host and port, without the credentials.
+    broker: String,
+    topic_prefix: String,
+    discovery_prefix: String,
+}
+
+impl Mqtt {
+    /// Connect to `mqtt://host[:port]`, or `mqtts://` for TLS. Credentials
+    /// can be in the URL. The connection is kept up in the background.
+    pub fn connect(
+        url: &str,
+        credentials: Option<(String, String)>,
+        topic_prefix: String,
+        discovery_prefix: String,
+    ) -> Result<Self> {
+        let url = Url::parse(url).map_err(|err| format!("invalid MQTT URL `{}`: {}", url, err))?;
+        let (tls, default_port) = match url.scheme() {
+            "mqtt" | "tcp" => (false, 1883),
+            "mqtts" | "ssl" => (true, 8883),
+            scheme => return Err(format!("unsupported MQTT scheme `{}`", scheme).into()),
+        };
+        let Some(host) = url.host_str() else {
+            return Err(format!("no host in MQTT URL `{}`", url).into());
+        };
+
+        let port = url.port().unwrap_or(default_port);
+        let client_id = format!("gnosispay-cli-{}", std::process::id());
+        let mut options = MqttOptions::new(client_id, host, port);
+        options.set_keep_alive(KEEP_ALIVE);
+        if tls {
+            options.set_transport(Transport::tls_with_default_config());
+        }
+
+        let credentials = credentials.or_else(|| {
+            (!url.username().is_empty()).then(|| {
+                (
+                    url.username().to_string(),
+                    url.password().unwrap_or_default().to_string(),
+                )
+            })
+        });
+        if let Some((username, password)) = credentials {
+            options.set_credentials(username, password);
+        }
+
+        // Home Assistant marks the sensors unavailable when the monitor stops.
+        let status = format!("{}/status", topic_prefix);
+        options.set_last_will(LastWill::new(&status, "offline", QoS::AtLeastOnce, true));
+
+        let (client, mut eventloop) = AsyncClient::new(options, CAPACITY);
+
+        let online = client.clone();
+        tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        println!("{}", "[+] Connected to the MQTT broker".green());
+                        if let Err(err) =
+                            online.try_publish(&status, QoS::AtLeastOnce, true, "online")
+                        {
+                            eprintln!("{}", format!("[-] MQTT: {}", err).red());
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{}", format!("[-] MQTT: {}", err).red());
+                        tokio::time::sleep(RECONNECT_DELAY).await;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            client,
+            broker: format!("{}:{}", host, port),
+            topic_prefix,
+            discovery_prefix,
+        })
+    }
+
+    pub fn broker(&self) -> &str {
+        &self.broker
+    }
+
+    /// Topic of a wallet value, e.g. `gnosispay/0xabc…/balance`.
+    fn topic(&self, wallet: Address, name: &str) -> String {
+        format!("{}/{:#x}/{}", self.topic_prefix, wallet, name)
+    }
+
+    /// Queue a message, never waits for the broker.
+    fn publish(&self, topic: String, retain: bool, payload: String) -> Result<()> {
+        self.client
+            .try_publish(topic, QoS::AtLeastOnce, retain, payload)
+            .map_err(|err| format!("cannot publish to MQTT: {}", err).into())
+    }
+
+    /// Home Assistant discovery payloads of the sensors of a wallet, with
+    /// their topics.
+    pub fn discovery(&self, label: &str, wallet: Address) -> Vec<(String, Value)> {
+        let node = format!("gnosispay_{:x}", wallet);
+        let device = json!({
+            "identifiers": [node],
+            "name": label,
+            "manufacturer": "Gnosis Pay",
+            "model": "Safe",
+        });
+        let availability = format!("{}/status", self.topic_prefix);
+
+        let sensors = [
+            (
+                "balance",
+                json!({
+                    "name": "Gnosis Pay balance",
+                    "state_topic": self.topic(wallet, "balance"),
+                    "device_class": "monetary",
+                    "unit_of_measurement": "EUR",
+                    "state_class": "total",
+                }),
+            ),
+            (
+                "xdai_balance",
+                json!({
+                    "name": "xDAI balance",
+                    "state_topic": self.topic(wallet, "xdai_balance"),
+                    "unit_of_measurement": "xDAI",
+                    "state_class": "measurement",
+                    "icon": "mdi:gas-station",
+                }),
+            ),
+            (
+                "last_purchase",
+                json!({
+                    "name": "Last purchase merchant",
+                    "state_topic": self.topic(wallet, "last_purchase"),
+                    "value_template": "{{ value_json.merchant }}",
+                    "json_attributes_topic": self.topic(wallet, "last_purchase"),
+                    "icon": "mdi:store",
+                }),
+            ),
+            (
+                "spent_today",
+                json!({
+                    "name": "Spent today",
+                    "state_topic": self.topic(wallet, "spent_today"),
+                    "device_class": "monetary",
+                    "unit_of_measurement": "EUR",
+                    // Monetary sensors only take `total`, reset at midnight.
+                    "state_class": "total",
+                    "value_template": "{{ value_json.amount }}",
+                    "last_reset_value_template": "{{ value_json.last_reset }}",
+                }),
+            ),
+        ];
+
+        sensors
+            .into_iter()
+            .map(|(object, mut config)| {
+                config["unique_id"] = json!(format!("{}_{}", node, object));
+                config["availability_topic"] = json!(availability);
+                config["device"] = device.clone();
+                let topic = format!(
+                    "{}/sensor/{}/{}/config",
+                    self.discovery_prefix, node, object
+                );
+                (topic, config)
+            })
+            .collect()
+    }
+
+    /// Announce the sensors of a wallet to Home Assistant.
+    pub fn announce(&self, label: &str, wallet: Address) -> Result<()> {
+        for (topic, config) in self.discovery(label, wallet) {
+            self.publish(topic, true, config.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Publish a transfer, and the last purchase for card payments with a merchant.
+    pub fn transfer(&self, transfer: &TransferMessage) -> Result<()> {
+        let payload = serde_json::to_string(transfer)?;
+        self.publish(
+            self.topic(transfer.wallet, "transfer"),
+            false,
+            payload.clone(),
+        )?;
+
+        if transfer.kind == Kind::Card && transfer.merchant.is_some() {
+            self.publish(self.topic(transfer.wallet, "last_purchase"), true, payload)?;
+        }
+        Ok(())
+    }
+
+    pub fn spent_today(&self, wallet: Address, amount: f64, now: DateTime<Local>) -> Result<()> {
+        self.publish(
+            self.topic(wallet, "spent_today"),
+            true,
+            spent_today_state(amount, now).to_string(),
+        )
+    }
+
+    pub fn balances(&self, wallet: Address, eure: f64, xdai: f64) -> Result<()> {
+        self.publish(self.topic(wallet, "balance"), true, format!("{:.2}", eure))?;
+        self.publish(
+            self.topic(wallet, "xdai_balance"),
+            true,
+            format!("{:.4}", xdai),
+        )
+    }
+}
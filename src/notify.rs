@@ -1,45 +1,215 @@
 use crate::prelude::*;
-use reqwest;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
+/// Pushover message priority.
+/// See <https://pushover.net/api#priority>
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Lowest,
+    Low,
+    #[default]
+    Normal,
+    High,
+    Emergency,
+}
+
+impl Priority {
+    fn value(&self) -> i8 {
+        match self {
+            Priority::Lowest => -2,
+            Priority::Low => -1,
+            Priority::Normal => 0,
+            Priority::High => 1,
+            Priority::Emergency => 2,
+        }
+    }
+}
+
+/// A Pushover message and its optional parameters.
+#[derive(Debug, Clone, Default)]
+pub struct Message {
+    pub title: String,
+    pub message: String,
+    pub priority: Priority,
+    pub sound: Option<String>,
+    pub device: Option<String>,
+    pub url: Option<String>,
+    pub url_title: Option<String>,
+    /// Emergency priority only: seconds between retries (minimum 30).
+    pub retry: Option<u32>,
+    /// Emergency priority only: seconds before giving up retrying (maximum 10800).
+    pub expire: Option<u32>,
+}
+
+impl Message {
+    pub fn new(title: String, message: String) -> Self {
+        Self {
+            title,
+            message,
+            ..Default::default()
+        }
+    }
+
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn url(mut self, url: String, url_title: Option<String>) -> Self {
+        self.url = Some(url);
+        self.url_title = url_title;
+        self
+    }
+
+    pub fn emergency(mut self, retry: u32, expire: u32) -> Self {
+        self.priority = Priority::Emergency;
+        self.retry = Some(retry);
+        self.expire = Some(expire);
+        self
+    }
+}
+
+/// Error returned by the Pushover API.
+#[derive(Debug)]
+pub struct PushOverError {
+    pub status: u16,
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for PushOverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Pushover request failed ({}): {}",
+            self.status,
+            self.errors.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for PushOverError {}
+
+#[derive(Debug, Deserialize)]
+#[allow(dead_code)]
+struct PushOverResponse {
+    status: i32,
+    request: Option<String>,
+    receipt: Option<String>,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+/// Pushover client for a single recipient.
 pub struct PushOverNotify {
     url: String,
     user: String,
     token: String,
+    device: Option<String>,
+    sound: Option<String>,
+    client: reqwest::Client,
 }
 
 impl PushOverNotify {
+    /// Default retry interval, in seconds, for emergency messages.
+    pub const EMERGENCY_RETRY: u32 = 60;
+    /// Default expiration, in seconds, for emergency messages.
+    pub const EMERGENCY_EXPIRE: u32 = 3600;
+
+    /// Default messages endpoint.
+    pub const URL: &str = "https://api.pushover.net/1/messages.json";
+
     pub fn new(user: String, token: String) -> Self {
         Self {
-            url: String::from("https://api.pushover.net/1/messages.json"),
+            url: String::from(Self::URL),
             user,
             token,
+            device: None,
+            sound: None,
+            client: reqwest::Client::new(),
         }
     }
 
+    /// Use another messages endpoint.
+    pub fn with_url(mut self, url: String) -> Self {
+        self.url = url;
+        self
+    }
+
+    /// Share a client, and its connection pool, with other services.
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Default device used when a message does not target one.
+    pub fn with_device(mut self, device: Option<String>) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// Default sound used when a message does not set one.
+    pub fn with_sound(mut self, sound: Option<String>) -> Self {
+        self.sound = sound;
+        self
+    }
+
     /// Send a notification with title/message.
     pub async fn notify(&self, title: String, message: String) -> Result<()> {
-        let content = format!(
-            "token={}&user={}&title={}&message={}",
-            self.token, self.user, title, message
-        );
-
-        let client = reqwest::Client::builder().build()?;
-
-        /* Send the request. */
-        let response = client
-            .post(&self.url)
-            .header("Content-type", "application/x-www-form-urlencoded")
-            .body(content)
-            .send()
-            .await?;
-
-        match response.status() {
-            reqwest::StatusCode::OK => {}
-            _other => {
-                panic!("[-] Unexpected error: {:?}", response.text().await);
-            }
+        self.send(&Message::new(title, message)).await
+    }
+
+    /// Send a message with all its parameters.
+    pub async fn send(&self, message: &Message) -> Result<()> {
+        let mut params = vec![
+            ("token", self.token.clone()),
+            ("user", self.user.clone()),
+            ("title", message.title.clone()),
+            ("message", message.message.clone()),
+            ("priority", message.priority.value().to_string()),
+        ];
+
+        if let Some(sound) = message.sound.as_ref().or(self.sound.as_ref()) {
+            params.push(("sound", sound.clone()));
+        }
+        if let Some(device) = message.device.as_ref().or(self.device.as_ref()) {
+            params.push(("device", device.clone()));
+        }
+        if let Some(url) = &message.url {
+            params.push(("url", url.clone()));
+        }
+        if let Some(url_title) = &message.url_title {
+            params.push(("url_title", url_title.clone()));
+        }
+
+        // Emergency messages are rejected without retry and expire.
+        if message.priority == Priority::Emergency {
+            let retry = message.retry.unwrap_or(Self::EMERGENCY_RETRY).max(30);
+            let expire = message.expire.unwrap_or(Self::EMERGENCY_EXPIRE).min(10800);
+            params.push(("retry", retry.to_string()));
+            params.push(("expire", expire.to_string()));
         }
 
-        Ok(())
+        /* Send the request, the body is form encoded by reqwest. */
+        let response = self.client.post(&self.url).form(&params).send().await?;
+        let status = response.status();
+
+        // Pushover answers with a JSON body, even on errors.
+        let body = response.json::<PushOverResponse>().await.ok();
+
+        match body {
+            Some(body) if status.is_success() && body.status == 1 => Ok(()),
+            Some(body) => Err(PushOverError {
+                status: status.as_u16(),
+                errors: body.errors,
+            }
+            .into()),
+            None => Err(PushOverError {
+                status: status.as_u16(),
+                errors: vec![String::from("invalid response")],
+            }
+            .into()),
+        }
     }
 }
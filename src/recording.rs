@@ -0,0 +1,49 @@
+use crate::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+
+/// Save raw API responses to a directory, or serve them back without network.
+#[derive(Debug, Clone)]
+pub enum Recording {
+    /// Responses are fetched and saved in the directory.
+    Record(PathBuf),
+    /// Responses are read from the directory, nothing is fetched.
+    Replay(PathBuf),
+}
+
+impl Recording {
+    fn path(&self, name: &str) -> PathBuf {
+        let dir = match self {
+            Recording::Record(dir) | Recording::Replay(dir) => dir,
+        };
+        dir.join(format!("{}.json", name))
+    }
+
+    /// A response was recorded under this name.
+    pub fn contains(&self, name: &str) -> bool {
+        self.path(name).exists()
+    }
+
+    /// Fetch the response body, saving it when recording or reading it back
+    /// when replaying.
+    pub async fn fetch<F>(recording: Option<&Self>, name: &str, fetch: F) -> Result<String>
+    where
+        F: Future<Output = Result<String>>,
+    {
+        match recording {
+            None => fetch.await,
+            Some(recording @ Recording::Replay(_)) => {
+                let path = recording.path(name);
+                fs::read_to_string(&path).map_err(|err| {
+                    format!("no recorded response `{}`: {}", path.display(), err).into()
+                })
+            }
+            Some(recording @ Recording::Record(dir)) => {
+                let body = fetch.await?;
+                fs::create_dir_all(dir)?;
+                fs::write(recording.path(name), &body)?;
+                Ok(body)
+            }
+        }
+    }
+}
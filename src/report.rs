@@ -0,0 +1,308 @@
+use crate::prelude::*;
+use crate::transactions::{Transaction, Transactions};
+use chrono::{Datelike, Days, Months, NaiveDate};
+use colored::Colorize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::str::FromStr;
+
+/// How transactions are grouped in a report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    Month,
+    /// ISO week.
+    Week,
+    Merchant,
+    /// Merchant category code.
+    Mcc,
+    Category,
+    Country,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "month" => Ok(GroupBy::Month),
+            "week" => Ok(GroupBy::Week),
+            "merchant" => Ok(GroupBy::Merchant),
+            "mcc" => Ok(GroupBy::Mcc),
+            "category" => Ok(GroupBy::Category),
+            "country" => Ok(GroupBy::Country),
+            _ => Err(format!(
+                "invalid grouping `{}`, expected one of month, week, merchant, mcc, category, country",
+                value
+            )),
+        }
+    }
+}
+
+impl GroupBy {
+    /// Time groupings are sorted by date and compared with the previous period.
+    fn is_period(&self) -> bool {
+        matches!(self, GroupBy::Month | GroupBy::Week)
+    }
+
+    /// First day of the period of a day, for time groupings.
+    fn period_start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            GroupBy::Week => date - Days::new(date.weekday().num_days_from_monday().into()),
+            _ => date.with_day(1).expect("every month has a first day"),
+        }
+    }
+
+    fn next_period(&self, start: NaiveDate) -> NaiveDate {
+        match self {
+            GroupBy::Week => start + Days::new(7),
+            _ => start + Months::new(1),
+        }
+    }
+
+    fn period_key(&self, date: NaiveDate) -> String {
+        match self {
+            GroupBy::Week => {
+                let week = date.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            _ => date.format("%Y-%m").to_string(),
+        }
+    }
+
+    fn key(&self, transaction: &Transaction) -> String {
+        match self {
+            GroupBy::Month | GroupBy::Week => self.period_key(transaction.date.date_naive()),
+            GroupBy::Merchant => transaction.name.clone(),
+            GroupBy::Mcc => transaction.mcc.clone().unwrap_or_else(|| String::from("-")),
+            GroupBy::Category => transaction
+                .category
+                .clone()
+                .unwrap_or_else(|| String::from("-")),
+            GroupBy::Country => transaction
+                .country
+                .clone()
+                .unwrap_or_else(|| String::from("-")),
+        }
+    }
+
+    /// Amounts of a transaction per group, a split transaction counts in the
+    /// category of each of its parts.
+    fn amounts(&self, transaction: &Transaction) -> Vec<(String, f64)> {
+        match self {
+            GroupBy::Category => transaction
+                .parts()
+                .into_iter()
+                .map(|part| {
+                    let category = part.category.unwrap_or("-");
+                    (category.to_string(), part.amount)
+                })
+                .collect(),
+            _ => vec![(self.key(transaction), transaction.amount)],
+        }
+    }
+}
+
+impl fmt::Display for GroupBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            GroupBy::Month => "Month",
+            GroupBy::Week => "Week",
+            GroupBy::Merchant => "Merchant",
+            GroupBy::Mcc => "MCC",
+            GroupBy::Category => "Category",
+            GroupBy::Country => "Country",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Totals of a group.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Row {
+    pub key: String,
+    pub count: usize,
+    /// Money received.
+    pub income: f64,
+    /// Money spent, as a positive amount.
+    pub expense: f64,
+    pub net: f64,
+    /// Change of the expense since the previous period.
+    pub expense_delta: Option<f64>,
+}
+
+impl Row {
+    fn add(&mut self, amount: f64) {
+        self.count += 1;
+        if amount > 0.0 {
+            self.income += amount;
+        } else {
+            self.expense -= amount;
+        }
+        self.net += amount;
+    }
+}
+
+/// Spending aggregated by period, merchant, category or country.
+#[derive(Debug, Clone, Serialize)]
+pub struct Report {
+    pub group_by: GroupBy,
+    pub rows: Vec<Row>,
+    pub total: Row,
+}
+
+impl Report {
+    pub fn new(transactions: &Transactions, group_by: GroupBy) -> Self {
+        let mut groups: BTreeMap<String, Row> = BTreeMap::new();
+        let mut total = Row {
+            key: String::from("Total"),
+            ..Row::default()
+        };
+
+        // Safe transactions without transfer move no money.
+        for transaction in transactions.transactions.values() {
+            if transaction.amount == 0.0 {
+                continue;
+            }
+
+            for (key, amount) in group_by.amounts(transaction) {
+                groups
+                    .entry(key.clone())
+                    .or_insert_with(|| Row {
+                        key,
+                        ..Row::default()
+                    })
+                    .add(amount);
+            }
+            total.add(transaction.amount);
+        }
+
+        let rows = if group_by.is_period() {
+            Self::periods(transactions, group_by, groups)
+        } else {
+            let mut rows: Vec<Row> = groups.into_values().collect();
+            rows.sort_by(|a, b| b.expense.total_cmp(&a.expense));
+            rows
+        };
+
+        Self {
+            group_by,
+            rows,
+            total,
+        }
+    }
+
+    /// Every period from the first transaction to the last one, those without
+    /// transactions included, each compared with the one before.
+    fn periods(
+        transactions: &Transactions,
+        group_by: GroupBy,
+        mut groups: BTreeMap<String, Row>,
+    ) -> Vec<Row> {
+        let dates = transactions
+            .transactions
+            .values()
+            .filter(|transaction| transaction.amount != 0.0)
+            .map(|transaction| transaction.date.date_naive());
+        let (Some(first), Some(last)) = (dates.clone().min(), dates.max()) else {
+            return Vec::new();
+        };
+
+        let mut rows: Vec<Row> = Vec::new();
+        let mut start = group_by.period_start(first);
+        while start <= last {
+            let key = group_by.period_key(start);
+            let mut row = groups.remove(&key).unwrap_or(Row {
+                key,
+                ..Row::default()
+            });
+            row.expense_delta = rows.last().map(|previous| row.expense - previous.expense);
+            rows.push(row);
+            start = group_by.next_period(start);
+        }
+        rows
+    }
+
+    /// Export the report in CSV format.
+    pub fn to_csv(&self, filename: String, delimiter: char) -> Result<()> {
+        let mut file = File::create(filename.clone())?;
+        let d = delimiter;
+
+        writeln!(
+            file,
+            "{}{d}Count{d}Income{d}Expense{d}Net{d}Expense delta",
+            self.group_by
+        )?;
+        for row in self.rows.iter().chain([&self.total]) {
+            writeln!(
+                file,
+                "{}{d}{}{d}{:.2}{d}{:.2}{d}{:.2}{d}{}",
+                row.key,
+                row.count,
+                row.income,
+                row.expense,
+                row.net,
+                row.expense_delta
+                    .map(|delta| format!("{:.2}", delta))
+                    .unwrap_or_default()
+            )?;
+        }
+
+        println!("{}", format!("[+] CSV export to `{}`.", filename).green());
+        Ok(())
+    }
+
+    /// Export the report in JSON format.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .rows
+            .iter()
+            .map(|row| row.key.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(self.group_by.to_string().len())
+            .max(self.total.key.len());
+
+        let header = format!(
+            "{:<width$}  {:>5}  {:>10}  {:>10}  {:>10}  {:>10}",
+            self.group_by.to_string(),
+            "Count",
+            "Income",
+            "Expense",
+            "Net",
+            "Delta"
+        );
+        writeln!(f, "{}", header.blue())?;
+
+        for row in self.rows.iter().chain([&self.total]) {
+            let delta = match row.expense_delta {
+                Some(delta) if delta > 0.0 => format!("{:>+10.2}", delta).red(),
+                Some(delta) => format!("{:>+10.2}", delta).green(),
+                None => format!("{:>10}", "").normal(),
+            };
+
+            let line = format!(
+                "{:<width$}  {:>5}  {:>10.2}  {:>10.2}  {:>10.2}  ",
+                row.key, row.count, row.income, row.expense, row.net
+            );
+
+            if std::ptr::eq(row, &self.total) {
+                writeln!(f, "{}{}", line.bold(), delta)?;
+            } else {
+                writeln!(f, "{}{}", line, delta)?;
+            }
+        }
+
+        Ok(())
+    }
+}
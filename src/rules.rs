@@ -0,0 +1,211 @@
+use crate::gnosispay::GnosisTransaction;
+use crate::notify::{Message, Priority};
+use crate::prelude::*;
+use chrono::{DateTime, Local, NaiveTime};
+use serde::{Deserialize, Deserializer};
+use std::fs;
+
+/// Where a notification is delivered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Channel {
+    #[default]
+    Pushover,
+    /// Only print to the console.
+    Console,
+    /// Drop the notification.
+    None,
+}
+
+/// What to do when a rule matches.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Action {
+    /// Keeps the priority of the message when not set.
+    pub priority: Option<Priority>,
+    #[serde(default)]
+    pub channel: Channel,
+    pub sound: Option<String>,
+    pub device: Option<String>,
+    pub retry: Option<u32>,
+    pub expire: Option<u32>,
+    /// Keep the priority even during quiet hours.
+    #[serde(default)]
+    pub ignore_quiet_hours: bool,
+}
+
+impl Action {
+    /// Apply the action's parameters to a message.
+    pub fn apply(&self, message: Message) -> Message {
+        Message {
+            priority: self.priority.unwrap_or(message.priority),
+            sound: self.sound.clone().or(message.sound),
+            device: self.device.clone().or(message.device),
+            retry: self.retry.or(message.retry),
+            expire: self.expire.or(message.expire),
+            ..message
+        }
+    }
+}
+
+/// A rule matches when all of its conditions are met.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Rule {
+    pub name: String,
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    /// Case insensitive substring of the merchant name.
+    pub merchant: Option<String>,
+    pub mcc: Option<Vec<String>>,
+    /// Merchant country, as alpha2 or alpha3 code.
+    pub country: Option<Vec<String>>,
+    /// Purchase made in another currency than the billing one.
+    pub foreign_currency: Option<bool>,
+    /// Total spent today, including this payment, is above.
+    pub daily_total_above: Option<f64>,
+    #[serde(flatten)]
+    pub action: Action,
+}
+
+/// Local time range where notifications are silenced.
+#[derive(Debug, Clone, Deserialize)]
+pub struct QuietHours {
+    #[serde(deserialize_with = "deserialize_time")]
+    pub start: NaiveTime,
+    #[serde(deserialize_with = "deserialize_time")]
+    pub end: NaiveTime,
+}
+
+impl QuietHours {
+    /// Check if the time is within quiet hours, ranges may wrap around midnight.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            time >= self.start && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// A card payment seen by the monitor.
+#[derive(Debug, Clone)]
+pub struct Payment {
+    pub amount: f64,
+    pub time: DateTime<Local>,
+    pub daily_total: f64,
+    /// Details from Gnosis Pay, when available.
+    pub details: Option<GnosisTransaction>,
+}
+
+impl Payment {
+    fn is_foreign_currency(&self) -> Option<bool> {
+        self.details
+            .as_ref()
+            .map(|d| d.transaction_currency.code != d.billing_currency.code)
+    }
+}
+
+/// Monitor alerting rules, loaded from a TOML file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Rules {
+    pub quiet_hours: Option<QuietHours>,
+    /// Action when no rule matches.
+    pub default: Option<Action>,
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<Rule>,
+}
+
+impl Rules {
+    /// Load rules from a TOML file.
+    pub fn load(filename: &str) -> Result<Self> {
+        let content = fs::read_to_string(filename)?;
+        let rules = toml::from_str(&content)?;
+        Ok(rules)
+    }
+
+    /// Find the action for a payment, with the name of the rule. The first
+    /// matching rule wins, then the default action. Without them, the message
+    /// is sent as is, but still silenced during quiet hours.
+    pub fn evaluate(&self, payment: &Payment) -> (Option<String>, Action) {
+        let (name, mut action) = match self.rules.iter().find(|rule| rule.matches(payment)) {
+            Some(rule) => (Some(rule.name.clone()), rule.action.clone()),
+            None => match &self.default {
+                Some(default) => (Some(String::from("default")), default.clone()),
+                None => (None, Action::default()),
+            },
+        };
+
+        if let Some(quiet_hours) = &self.quiet_hours
+            && !action.ignore_quiet_hours
+            && quiet_hours.contains(payment.time.time())
+        {
+            action.priority = Some(Priority::Lowest);
+        }
+
+        (name, action)
+    }
+}
+
+impl Rule {
+    pub fn matches(&self, payment: &Payment) -> bool {
+        let amount = payment.amount.abs();
+
+        if self.min_amount.is_some_and(|min| amount < min) {
+            return false;
+        }
+        if self.max_amount.is_some_and(|max| amount > max) {
+            return false;
+        }
+        if self
+            .daily_total_above
+            .is_some_and(|total| payment.daily_total <= total)
+        {
+            return false;
+        }
+        if let Some(foreign) = self.foreign_currency
+            && payment.is_foreign_currency() != Some(foreign)
+        {
+            return false;
+        }
+
+        // Conditions below need the Gnosis Pay details.
+        let details = payment.details.as_ref();
+
+        if let Some(merchant) = &self.merchant {
+            let Some(details) = details else { return false };
+            if !details
+                .merchant
+                .name
+                .to_lowercase()
+                .contains(&merchant.to_lowercase())
+            {
+                return false;
+            }
+        }
+        if let Some(mcc) = &self.mcc {
+            let Some(details) = details else { return false };
+            if !mcc.contains(&details.mcc) {
+                return false;
+            }
+        }
+        if let Some(country) = &self.country {
+            let Some(details) = details else { return false };
+            let merchant_country = &details.merchant.country;
+            if !country.iter().any(|c| {
+                c.eq_ignore_ascii_case(&merchant_country.alpha2)
+                    || c.eq_ignore_ascii_case(&merchant_country.alpha3)
+            }) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+fn deserialize_time<'de, D>(deserializer: D) -> core::result::Result<NaiveTime, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let time = String::deserialize(deserializer)?;
+    NaiveTime::parse_from_str(&time, "%H:%M").map_err(serde::de::Error::custom)
+}
@@ -0,0 +1,602 @@
+use crate::address_book::AddressBook;
+use crate::monerium::Token;
+use crate::monitor::IERC20;
+use crate::prelude::*;
+use alloy::primitives::{Address, B256, Bytes, U256, address, b256, utils::format_units};
+use alloy::providers::Provider;
+use alloy::rpc::types::Filter;
+use alloy::sol;
+use alloy::sol_types::{SolCall, SolEvent};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::fmt;
+
+sol!(
+    #[sol(rpc)]
+    interface ISafe {
+        event ExecutionSuccess(bytes32 txHash, uint256 payment);
+        event ExecutionFailure(bytes32 txHash, uint256 payment);
+        event ExecutionFromModuleSuccess(address indexed module);
+        event ExecutionFromModuleFailure(address indexed module);
+        function VERSION() external view returns (string memory);
+        function getOwners() external view returns (address[] memory);
+        function getThreshold() external view returns (uint256);
+        function nonce() external view returns (uint256);
+        function getModulesPaginated(address start, uint256 pageSize) external view returns (address[] memory array, address next);
+    }
+);
+
+sol!(
+    #[sol(rpc)]
+    interface IDelay {
+        event TransactionAdded(uint256 indexed queueNonce, bytes32 indexed txHash, address to, uint256 value, bytes data, uint8 operation);
+        function txCreatedAt(uint256 nonce) external view returns (uint256);
+        function txCooldown() external view returns (uint256);
+        function txExpiration() external view returns (uint256);
+        function txNonce() external view returns (uint256);
+        function queueNonce() external view returns (uint256);
+    }
+);
+
+sol!(
+    #[sol(rpc)]
+    interface IRoles {
+        function allowances(bytes32 key) external view returns (uint128 refill, uint128 maxRefill, uint64 period, uint128 balance, uint64 timestamp);
+    }
+);
+
+/// Start and end of the Safe modules linked list.
+const SENTINEL: Address = address!("0x0000000000000000000000000000000000000001");
+
+/// Modules read per call.
+const MODULES_PAGE_SIZE: u64 = 10;
+
+/// Blocks per `eth_getLogs` request, public RPCs reject larger ranges.
+const LOGS_BLOCK_RANGE: u64 = 10_000;
+
+/// Seconds between Gnosis Chain blocks.
+const BLOCK_TIME: u64 = 5;
+
+/// Roles allowance used by the card, `keccak256("SPENDING_ALLOWANCE")`.
+pub const SPENDING_ALLOWANCE_KEY: B256 =
+    b256!("0xfe687fc128d1915040376d20ccb1bf40d838ddd82bf9b0ba3da683cc2a251623");
+
+/// Topic of a Safe transaction executed by the owners.
+pub const EXECUTION_SUCCESS: B256 = ISafe::ExecutionSuccess::SIGNATURE_HASH;
+
+/// Topic of a Safe transaction that failed.
+pub const EXECUTION_FAILURE: B256 = ISafe::ExecutionFailure::SIGNATURE_HASH;
+
+/// Topic of a transaction executed by a module, such as the Delay queue.
+pub const EXECUTION_FROM_MODULE_SUCCESS: B256 = ISafe::ExecutionFromModuleSuccess::SIGNATURE_HASH;
+
+/// Topic of a module transaction that failed.
+pub const EXECUTION_FROM_MODULE_FAILURE: B256 = ISafe::ExecutionFromModuleFailure::SIGNATURE_HASH;
+
+/// Zodiac module enabled on the Safe.
+#[derive(Debug, Clone)]
+pub enum ModuleKind {
+    /// Owner transactions are queued before they can be executed.
+    Delay {
+        /// Seconds before a queued transaction can be executed.
+        cooldown: u64,
+        /// Seconds a transaction can be executed after the cooldown, 0 for ever.
+        expiration: u64,
+        /// Next transaction to execute.
+        tx_nonce: u64,
+        /// Next transaction to queue.
+        queue_nonce: u64,
+    },
+    /// Card spending allowance.
+    Roles,
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub address: Address,
+    pub kind: ModuleKind,
+}
+
+/// Configuration of the Safe behind a Gnosis Pay wallet.
+#[derive(Debug, Clone)]
+pub struct SafeInfo {
+    pub address: Address,
+    pub version: String,
+    pub owners: Vec<Address>,
+    pub threshold: u64,
+    pub nonce: u64,
+    pub modules: Vec<Module>,
+}
+
+impl SafeInfo {
+    /// Read the Safe configuration over RPC.
+    pub async fn load<P: Provider>(provider: &P, address: Address) -> Result<Self> {
+        let safe = ISafe::new(address, provider);
+
+        let version = safe.VERSION().call().await?._0;
+        let owners = safe.getOwners().call().await?._0;
+        let threshold = safe.getThreshold().call().await?._0.try_into()?;
+        let nonce = safe.nonce().call().await?._0.try_into()?;
+
+        let mut modules = Vec::new();
+        let mut start = SENTINEL;
+        loop {
+            let page = safe
+                .getModulesPaginated(start, U256::from(MODULES_PAGE_SIZE))
+                .call()
+                .await?;
+
+            for module in page.array {
+                modules.push(Module {
+                    address: module,
+                    kind: module_kind(provider, module).await,
+                });
+            }
+
+            if page.next == SENTINEL || page.next == Address::ZERO {
+                break;
+            }
+            start = page.next;
+        }
+
+        Ok(Self {
+            address,
+            version,
+            owners,
+            threshold,
+            nonce,
+            modules,
+        })
+    }
+}
+
+impl SafeInfo {
+    /// Roles module holding the card spending allowance.
+    pub fn roles_module(&self) -> Option<Address> {
+        self.modules
+            .iter()
+            .find(|module| matches!(module.kind, ModuleKind::Roles))
+            .map(|module| module.address)
+    }
+
+    /// Delay modules enabled on the Safe.
+    pub fn delay_modules(&self) -> Vec<Address> {
+        self.modules
+            .iter()
+            .filter(|module| matches!(module.kind, ModuleKind::Delay { .. }))
+            .map(|module| module.address)
+            .collect()
+    }
+}
+
+/// Where a queued transaction stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueStatus {
+    /// Can still be cancelled before it becomes executable.
+    CoolingDown,
+    Executable,
+    Expired,
+}
+
+/// A transaction queued in the Delay module, waiting for its cooldown.
+#[derive(Debug, Clone)]
+pub struct QueuedTransaction {
+    pub nonce: u64,
+    pub hash: B256,
+    pub to: Address,
+    /// xDAI sent along.
+    pub value: U256,
+    pub data: Bytes,
+    pub created_at: DateTime<Utc>,
+    pub executable_at: DateTime<Utc>,
+    /// `None` when queued transactions never expire.
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl QueuedTransaction {
+    /// Build from a `TransactionAdded` event.
+    pub fn new(
+        event: IDelay::TransactionAdded,
+        created_at: DateTime<Utc>,
+        cooldown: u64,
+        expiration: u64,
+    ) -> Self {
+        let executable_at = created_at + chrono::Duration::seconds(cooldown as i64);
+        let expires_at =
+            (expiration > 0).then(|| executable_at + chrono::Duration::seconds(expiration as i64));
+
+        Self {
+            nonce: event.queueNonce.try_into().unwrap_or_default(),
+            hash: event.txHash,
+            to: event.to,
+            value: event.value,
+            data: event.data,
+            created_at,
+            executable_at,
+            expires_at,
+        }
+    }
+
+    /// Recipient and amount, when the transaction is an ERC-20 transfer.
+    pub fn transfer(&self) -> Option<(Address, U256)> {
+        let call = IERC20::transferCall::abi_decode(&self.data, true).ok()?;
+        Some((call.to, call.value))
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> QueueStatus {
+        if now < self.executable_at {
+            QueueStatus::CoolingDown
+        } else if self.expires_at.is_some_and(|expires_at| now >= expires_at) {
+            QueueStatus::Expired
+        } else {
+            QueueStatus::Executable
+        }
+    }
+
+    /// Human readable summary, e.g. `20.00 EURe to 0x…`, with the labels of
+    /// the addresses.
+    pub fn summary(&self, address_book: &AddressBook) -> String {
+        let recipient = |address| address_book.describe(address);
+
+        if let Some((to, amount)) = self.transfer() {
+            let token = [Token::Eure, Token::EureV1]
+                .into_iter()
+                .find(|token| token.address() == self.to);
+
+            return match token.map(|token| (token, format_units(amount, token.decimals()))) {
+                Some((token, Ok(amount))) => format!(
+                    "{:.2} {} to {}",
+                    amount.parse::<f64>().unwrap_or_default(),
+                    token,
+                    recipient(to)
+                ),
+                _ => format!(
+                    "{} of token {} to {}",
+                    amount,
+                    recipient(self.to),
+                    recipient(to)
+                ),
+            };
+        }
+
+        if self.data.is_empty() {
+            let value = format_units(self.value, "ether").unwrap_or_default();
+            format!("{} xDAI to {}", value, recipient(self.to))
+        } else {
+            format!(
+                "Call to {} with {} bytes of data",
+                recipient(self.to),
+                self.data.len()
+            )
+        }
+    }
+}
+
+/// Delay module settings and its pending transactions.
+#[derive(Debug, Clone)]
+pub struct DelayQueue {
+    pub module: Address,
+    pub cooldown: u64,
+    pub expiration: u64,
+    /// Next transaction to execute.
+    pub tx_nonce: u64,
+    /// Next transaction to queue.
+    pub queue_nonce: u64,
+    pub transactions: Vec<QueuedTransaction>,
+    /// Nonces still queued but past their expiration, left unread.
+    pub expired: Vec<u64>,
+    /// Labels of the recipients, in the summaries.
+    pub address_book: AddressBook,
+}
+
+impl DelayQueue {
+    /// Read the settings of a Delay module and the transactions still queued.
+    pub async fn load<P: Provider>(provider: &P, module: Address) -> Result<Self> {
+        let delay = IDelay::new(module, provider);
+
+        let cooldown = delay.txCooldown().call().await?._0.try_into()?;
+        let expiration = delay.txExpiration().call().await?._0.try_into()?;
+        let tx_nonce: u64 = delay.txNonce().call().await?._0.try_into()?;
+        let queue_nonce: u64 = delay.queueNonce().call().await?._0.try_into()?;
+
+        let mut transactions = Vec::new();
+        let mut expired = Vec::new();
+
+        // Transactions below `tx_nonce` were executed or skipped.
+        let now = Utc::now();
+        let mut created = HashMap::new();
+        for nonce in tx_nonce..queue_nonce {
+            let created_at = delay.txCreatedAt(U256::from(nonce)).call().await?._0;
+            let Some(created_at) = DateTime::from_timestamp(created_at.try_into()?, 0) else {
+                return Err("cannot parse date".into());
+            };
+
+            // Expired transactions can no longer be executed, they are not
+            // worth scanning back to the day they were queued.
+            let lifetime = chrono::Duration::seconds((cooldown + expiration) as i64);
+            if expiration > 0 && created_at + lifetime <= now {
+                expired.push(nonce);
+            } else {
+                created.insert(nonce, created_at);
+            }
+        }
+
+        if !created.is_empty() {
+            // Public RPCs reject logs over the whole history, so only the
+            // blocks since the oldest transaction are read, a range at a time.
+            let oldest = created.values().min().copied().unwrap_or(now);
+            let age = (now - oldest).num_seconds().max(0) as u64;
+            let latest = provider.get_block_number().await?;
+            let mut from = latest.saturating_sub(age / BLOCK_TIME + LOGS_BLOCK_RANGE);
+
+            let nonces: Vec<B256> = created
+                .keys()
+                .map(|&nonce| U256::from(nonce).into())
+                .collect();
+            while from <= latest && transactions.len() < nonces.len() {
+                let to = (from + LOGS_BLOCK_RANGE - 1).min(latest);
+                let filter = Filter::new()
+                    .address(module)
+                    .event_signature(IDelay::TransactionAdded::SIGNATURE_HASH)
+                    .topic1(nonces.clone())
+                    .from_block(from)
+                    .to_block(to);
+
+                for log in provider.get_logs(&filter).await? {
+                    let event = log.log_decode::<IDelay::TransactionAdded>()?.inner.data;
+                    let nonce: u64 = event.queueNonce.try_into()?;
+                    let Some(created_at) = created.get(&nonce).copied() else {
+                        continue;
+                    };
+
+                    transactions.push(QueuedTransaction::new(
+                        event, created_at, cooldown, expiration,
+                    ));
+                }
+                from = to + 1;
+            }
+        }
+
+        transactions.sort_by_key(|transaction| transaction.nonce);
+
+        Ok(Self {
+            module,
+            cooldown,
+            expiration,
+            tx_nonce,
+            queue_nonce,
+            transactions,
+            expired,
+            address_book: AddressBook::default(),
+        })
+    }
+
+    /// Name the recipients after the labels of the user.
+    pub fn with_address_book(mut self, address_book: AddressBook) -> Self {
+        self.address_book = address_book;
+        self
+    }
+}
+
+impl fmt::Display for DelayQueue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {}",
+            "Delay".blue(),
+            self.module.to_string().yellow()
+        )?;
+        writeln!(f, "{}: {}s", "Cooldown".blue(), self.cooldown)?;
+        writeln!(f, "{}: {}s", "Expiration".blue(), self.expiration)?;
+
+        if self.transactions.is_empty() {
+            writeln!(f, "{}", "No transaction queued.".green())?;
+        }
+
+        let now = Utc::now();
+        for transaction in &self.transactions {
+            writeln!(f)?;
+            writeln!(f, "{}: {}", "Nonce".blue(), transaction.nonce)?;
+            writeln!(f, "{}: {}", "Hash".blue(), transaction.hash)?;
+            writeln!(f, "{}: {}", "Target".blue(), transaction.to)?;
+            writeln!(f, "{}: {}", "Value".blue(), transaction.value)?;
+            writeln!(
+                f,
+                "{}: {}",
+                "Summary".blue(),
+                transaction.summary(&self.address_book)
+            )?;
+            writeln!(
+                f,
+                "{}: {}",
+                "Queued".blue(),
+                transaction.created_at.format("%Y-%m-%d %H:%M:%S")
+            )?;
+
+            let status = match transaction.status(now) {
+                QueueStatus::CoolingDown => format!(
+                    "executable at {}, {} min left to cancel",
+                    transaction.executable_at.format("%Y-%m-%d %H:%M:%S"),
+                    (transaction.executable_at - now).num_minutes()
+                )
+                .red(),
+                QueueStatus::Executable => match transaction.expires_at {
+                    Some(expires_at) => format!(
+                        "executable until {}",
+                        expires_at.format("%Y-%m-%d %H:%M:%S")
+                    )
+                    .yellow(),
+                    None => "executable".yellow(),
+                },
+                QueueStatus::Expired => "expired".normal(),
+            };
+            writeln!(f, "{}: {}", "Status".blue(), status)?;
+        }
+
+        if !self.expired.is_empty() {
+            let nonces: Vec<String> = self.expired.iter().map(u64::to_string).collect();
+            writeln!(f)?;
+            writeln!(f, "{}: {}", "Expired nonces".blue(), nonces.join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Card spending allowance, refilled every period up to a maximum.
+#[derive(Debug, Clone, Copy)]
+pub struct Allowance {
+    /// Amount added every period.
+    pub refill: U256,
+    /// Refills stop at this balance.
+    pub max_refill: U256,
+    /// Seconds between refills, 0 when never refilled.
+    pub period: u64,
+    /// Balance at `timestamp`.
+    pub balance: U256,
+    /// Last refill.
+    pub timestamp: u64,
+}
+
+impl Allowance {
+    /// Read the card spending allowance from the Roles module.
+    pub async fn load<P: Provider>(provider: &P, module: Address) -> Result<Self> {
+        let allowance = IRoles::new(module, provider)
+            .allowances(SPENDING_ALLOWANCE_KEY)
+            .call()
+            .await?;
+
+        Ok(Self {
+            refill: U256::from(allowance.refill),
+            max_refill: U256::from(allowance.maxRefill),
+            period: allowance.period,
+            balance: U256::from(allowance.balance),
+            timestamp: allowance.timestamp,
+        })
+    }
+
+    /// Read the allowance of a Safe, `None` without Roles module.
+    pub async fn of_safe<P: Provider>(provider: &P, safe: Address) -> Result<Option<Self>> {
+        let info = SafeInfo::load(provider, safe).await?;
+        match info.roles_module() {
+            Some(module) => Ok(Some(Self::load(provider, module).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Balance and last refill at `now`, counting the refills since the last
+    /// spending, the same way the Roles module does.
+    pub fn accrued(&self, now: u64) -> (U256, u64) {
+        if self.period == 0 || now < self.timestamp + self.period {
+            return (self.balance, self.timestamp);
+        }
+
+        let intervals = (now - self.timestamp) / self.period;
+        let balance = if self.balance < self.max_refill {
+            (self.balance + self.refill * U256::from(intervals)).min(self.max_refill)
+        } else {
+            self.balance
+        };
+
+        (balance, self.timestamp + intervals * self.period)
+    }
+
+    /// Amount left to spend at `now`.
+    pub fn remaining(&self, now: DateTime<Utc>) -> U256 {
+        self.accrued(now.timestamp().max(0) as u64).0
+    }
+
+    /// When the next refill happens, `None` when never refilled.
+    pub fn next_refill(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.period == 0 {
+            return None;
+        }
+        let (_, timestamp) = self.accrued(now.timestamp().max(0) as u64);
+        DateTime::from_timestamp((timestamp + self.period) as i64, 0)
+    }
+}
+
+/// Recognize a module by the functions it answers to.
+async fn module_kind<P: Provider>(provider: &P, address: Address) -> ModuleKind {
+    let delay = IDelay::new(address, provider);
+    if let Ok(cooldown) = delay.txCooldown().call().await {
+        let number = |value: Option<U256>| {
+            value
+                .and_then(|value| u64::try_from(value).ok())
+                .unwrap_or_default()
+        };
+
+        return ModuleKind::Delay {
+            cooldown: number(Some(cooldown._0)),
+            expiration: number(delay.txExpiration().call().await.ok().map(|r| r._0)),
+            tx_nonce: number(delay.txNonce().call().await.ok().map(|r| r._0)),
+            queue_nonce: number(delay.queueNonce().call().await.ok().map(|r| r._0)),
+        };
+    }
+
+    let roles = IRoles::new(address, provider);
+    if roles.allowances(B256::ZERO).call().await.is_ok() {
+        return ModuleKind::Roles;
+    }
+
+    ModuleKind::Unknown
+}
+
+impl fmt::Display for SafeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {}",
+            "Safe".blue(),
+            self.address.to_string().yellow()
+        )?;
+        writeln!(f, "{}: {}", "Version".blue(), self.version)?;
+        writeln!(f, "{}: {}", "Nonce".blue(), self.nonce)?;
+        writeln!(
+            f,
+            "{}: {} of {}",
+            "Threshold".blue(),
+            self.threshold,
+            self.owners.len()
+        )?;
+
+        writeln!(f, "{}:", "Owners".blue())?;
+        for owner in &self.owners {
+            writeln!(f, "  {}", owner)?;
+        }
+
+        writeln!(f, "{}:", "Modules".blue())?;
+        for module in &self.modules {
+            match &module.kind {
+                ModuleKind::Delay {
+                    cooldown,
+                    expiration,
+                    tx_nonce,
+                    queue_nonce,
+                } => {
+                    writeln!(f, "  {} {}", module.address, "Delay".green())?;
+                    writeln!(f, "    Cooldown: {}s", cooldown)?;
+                    writeln!(f, "    Expiration: {}s", expiration)?;
+                    writeln!(
+                        f,
+                        "    Queue: {} executed, {} queued",
+                        tx_nonce, queue_nonce
+                    )?;
+                }
+                ModuleKind::Roles => {
+                    writeln!(
+                        f,
+                        "  {} {}",
+                        module.address,
+                        "Roles (spending allowance)".green()
+                    )?;
+                }
+                ModuleKind::Unknown => writeln!(f, "  {} {}", module.address, "Unknown".red())?,
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+use crate::prelude::*;
+use age::secrecy::SecretString;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+
+/// Names of the credentials that can be stored.
+pub const SESSION: &str = "session";
+pub const SESSION_TOKEN: &str = "session_token";
+pub const GNOSISSCAN_API_KEY: &str = "gnosisscan_api_key";
+pub const PUSHOVER_USER: &str = "pushover_user";
+pub const PUSHOVER_TOKEN: &str = "pushover_token";
+pub const MQTT_PASSWORD: &str = "mqtt_password";
+
+/// Environment variable holding the passphrase, for headless machines.
+const PASSPHRASE_ENV: &str = "GNOSISPAY_PASSPHRASE";
+
+/// Credentials encrypted with a passphrase using age.
+#[derive(Debug, Default)]
+pub struct Secrets {
+    passphrase: Option<SecretString>,
+    values: BTreeMap<String, String>,
+}
+
+/// Decrypted once per run, so the passphrase is only asked once.
+static SECRETS: Mutex<Option<Secrets>> = Mutex::new(None);
+
+/// Asks the passphrase when it is not in the environment, set by the application.
+static PROMPT: OnceLock<fn() -> Result<String>> = OnceLock::new();
+
+/// Ask the passphrase with `prompt` when `GNOSISPAY_PASSPHRASE` is not set,
+/// e.g. on the terminal. Without it, the environment variable is required.
+pub fn prompt_with(prompt: fn() -> Result<String>) {
+    let _ = PROMPT.set(prompt);
+}
+
+impl Secrets {
+    /// Where the encrypted credentials are stored.
+    pub fn path() -> Result<PathBuf> {
+        let Some(dir) = dirs::data_dir() else {
+            return Err("cannot find the data directory".into());
+        };
+        Ok(dir.join("gnosispay-cli").join("secrets.age"))
+    }
+
+    fn passphrase() -> Result<SecretString> {
+        let passphrase = match std::env::var(PASSPHRASE_ENV) {
+            Ok(passphrase) => passphrase,
+            Err(_) => match PROMPT.get() {
+                Some(prompt) => prompt()?,
+                None => {
+                    return Err(format!("set `{}` to open the secrets", PASSPHRASE_ENV).into());
+                }
+            },
+        };
+        Ok(SecretString::from(passphrase))
+    }
+
+    /// Open and decrypt the store, an empty store is returned if it does not exist.
+    pub fn open() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let passphrase = Self::passphrase()?;
+        let identity = age::scrypt::Identity::new(passphrase.clone());
+        let plaintext = age::decrypt(&identity, &fs::read(&path)?)
+            .map_err(|err| format!("cannot decrypt `{}`: {}", path.display(), err))?;
+
+        Ok(Self {
+            passphrase: Some(passphrase),
+            values: serde_json::from_slice(&plaintext)?,
+        })
+    }
+
+    /// Encrypt and store all credentials, readable by the current user only.
+    pub fn save(&mut self) -> Result<PathBuf> {
+        let passphrase = match self.passphrase.clone() {
+            Some(passphrase) => passphrase,
+            None => {
+                let passphrase = Self::passphrase()?;
+                self.passphrase = Some(passphrase.clone());
+                passphrase
+            }
+        };
+
+        let recipient = age::scrypt::Recipient::new(passphrase);
+        let ciphertext = age::encrypt(&recipient, &serde_json::to_vec(&self.values)?)?;
+
+        let path = Self::path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Written aside then renamed, an interrupted save keeps the old store.
+        let mut temporary = path.clone().into_os_string();
+        temporary.push(".tmp");
+
+        // Never readable by others, not even before the permissions are set.
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(&temporary)?;
+
+        // The mode only applies on creation, not to a file left over.
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        }
+        file.write_all(&ciphertext)?;
+        file.sync_all()?;
+        fs::rename(&temporary, &path)?;
+
+        Ok(path)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&String> {
+        self.values.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: String) {
+        self.values.insert(name.to_string(), value);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.values.remove(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.values.keys()
+    }
+
+    /// Look up a credential, decrypting the store on first use.
+    pub fn lookup(name: &str) -> Result<Option<String>> {
+        if !Self::path()?.exists() {
+            return Ok(None);
+        }
+
+        let mut secrets = SECRETS.lock().unwrap();
+        if secrets.is_none() {
+            *secrets = Some(Self::open()?);
+        }
+
+        Ok(secrets.as_ref().and_then(|s| s.get(name).cloned()))
+    }
+
+    /// Use the value given on the command line, otherwise the stored one.
+    pub fn resolve(value: Option<String>, name: &str) -> Result<String> {
+        if let Some(value) = value {
+            return Ok(value);
+        }
+
+        match Self::lookup(name)? {
+            Some(value) => Ok(value),
+            None => Err(format!(
+                "`{}` is not set, pass it as argument or run `secrets set {}`",
+                name, name
+            )
+            .into()),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::transactions::{Kind, Transaction};
+use chrono::{DateTime, Days, Months, Utc};
+use colored::Colorize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Charges needed before a merchant is called a subscription.
+const MIN_CHARGES: usize = 3;
+
+/// Share of the intervals that must match the cadence, a late charge is fine.
+const MIN_REGULARITY: f64 = 0.75;
+
+/// How far a charge can be from the usual amount, as a share of it.
+const AMOUNT_TOLERANCE: f64 = 0.5;
+
+/// How far a charge can be from the usual amount before it is another
+/// price, as a share of it. Charges in another currency vary by a few cents.
+const PRICE_TOLERANCE: f64 = 0.03;
+
+/// Two amounts are different prices, not exchange rate noise.
+fn other_price(amount: f64, usual: f64) -> bool {
+    (amount - usual).abs() > PRICE_TOLERANCE * usual.abs()
+}
+
+/// Name of the merchant, as grouped.
+fn merchant_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+/// Middle value of amounts, sorts them.
+fn median(amounts: &mut [f64]) -> f64 {
+    amounts.sort_by(f64::total_cmp);
+    amounts[amounts.len() / 2]
+}
+
+/// Time between two charges of a subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cadence {
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Cadence {
+    const ALL: [Cadence; 4] = [
+        Cadence::Weekly,
+        Cadence::Monthly,
+        Cadence::Quarterly,
+        Cadence::Yearly,
+    ];
+
+    /// Interval in days accepted for the cadence.
+    fn days(&self) -> (i64, i64) {
+        match self {
+            Cadence::Weekly => (6, 8),
+            Cadence::Monthly => (26, 35),
+            Cadence::Quarterly => (84, 98),
+            Cadence::Yearly => (350, 380),
+        }
+    }
+
+    fn matches(&self, days: i64) -> bool {
+        let (min, max) = self.days();
+        (min..=max).contains(&days)
+    }
+
+    /// Date of the charge following one on `date`.
+    pub fn next(&self, date: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Cadence::Weekly => date.checked_add_days(Days::new(7)),
+            Cadence::Monthly => date.checked_add_months(Months::new(1)),
+            Cadence::Quarterly => date.checked_add_months(Months::new(3)),
+            Cadence::Yearly => date.checked_add_months(Months::new(12)),
+        }
+    }
+}
+
+impl fmt::Display for Cadence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let cadence = match self {
+            Cadence::Weekly => "weekly",
+            Cadence::Monthly => "monthly",
+            Cadence::Quarterly => "quarterly",
+            Cadence::Yearly => "yearly",
+        };
+        write!(f, "{}", cadence)
+    }
+}
+
+/// Recurring card payments to a merchant.
+#[derive(Debug, Clone)]
+pub struct Subscription {
+    pub merchant: String,
+    pub cadence: Cadence,
+    pub charges: usize,
+    /// Latest amount charged, expected for the next charge.
+    pub amount: f64,
+    /// Median of the charges since the latest price change.
+    pub usual_amount: f64,
+    /// Amount before the latest price change.
+    pub previous_amount: Option<f64>,
+    pub last_charge: DateTime<Utc>,
+    pub next_charge: Option<DateTime<Utc>>,
+}
+
+impl Subscription {
+    /// Find subscriptions among card payments, by regular intervals and
+    /// similar amounts. Sorted by next charge.
+    pub fn detect<'a>(transactions: impl IntoIterator<Item = &'a Transaction>) -> Vec<Self> {
+        let mut merchants: HashMap<String, Vec<&Transaction>> = HashMap::new();
+        for transaction in transactions {
+            if transaction.kind == Kind::Card && transaction.amount < 0.0 {
+                merchants
+                    .entry(merchant_key(&transaction.name))
+                    .or_default()
+                    .push(transaction);
+            }
+        }
+
+        let mut subscriptions: Vec<Self> = merchants
+            .into_values()
+            .filter_map(|mut charges| {
+                charges.sort_by_key(|transaction| transaction.date);
+                Self::from_charges(&charges)
+            })
+            .collect();
+        subscriptions.sort_by(|a, b| {
+            a.next_charge
+                .cmp(&b.next_charge)
+                .then_with(|| a.merchant.cmp(&b.merchant))
+        });
+        subscriptions
+    }
+
+    /// Subscription from the charges of a merchant, sorted by date.
+    fn from_charges(charges: &[&Transaction]) -> Option<Self> {
+        if charges.len() < MIN_CHARGES {
+            return None;
+        }
+
+        let intervals: Vec<i64> = charges
+            .windows(2)
+            .map(|pair| (pair[1].date - pair[0].date).num_days())
+            .collect();
+        let cadence = Cadence::ALL.into_iter().find(|cadence| {
+            let regular = intervals
+                .iter()
+                .filter(|days| cadence.matches(**days))
+                .count();
+            regular as f64 >= MIN_REGULARITY * intervals.len() as f64
+        })?;
+
+        let mut amounts: Vec<f64> = charges.iter().map(|charge| -charge.amount).collect();
+        let last = *amounts.last()?;
+
+        // Charges at the current price, the latest ones.
+        let current = amounts
+            .iter()
+            .rev()
+            .take_while(|amount| !other_price(**amount, last))
+            .count();
+        let previous = amounts.iter().rev().nth(current).copied();
+        let mut recent = amounts[amounts.len() - current..].to_vec();
+        let usual_amount = median(&mut recent);
+
+        let median = median(&mut amounts);
+        if amounts
+            .iter()
+            .any(|amount| (amount - median).abs() > AMOUNT_TOLERANCE * median)
+        {
+            return None;
+        }
+
+        let last_charge = charges.last()?;
+        Some(Self {
+            merchant: last_charge.name.trim().to_string(),
+            cadence,
+            charges: charges.len(),
+            amount: last,
+            usual_amount,
+            previous_amount: previous,
+            last_charge: last_charge.date,
+            next_charge: cadence.next(last_charge.date),
+        })
+    }
+
+    /// The latest price change was an increase.
+    pub fn price_increased(&self) -> bool {
+        self.previous_amount
+            .is_some_and(|previous| self.amount > previous)
+    }
+
+    /// A charge of this subscription at another price than the usual one,
+    /// beyond exchange rate changes.
+    pub fn differs(&self, amount: f64) -> bool {
+        other_price(amount, self.usual_amount)
+    }
+
+    /// No charge for longer than the cadence allows, e.g. cancelled.
+    pub fn is_lapsed(&self, now: DateTime<Utc>) -> bool {
+        let (_, max) = self.cadence.days();
+        (now - self.last_charge).num_days() > max
+    }
+
+    /// Subscription of the merchant of a card payment.
+    pub fn find<'a>(subscriptions: &'a [Self], transaction: &Transaction) -> Option<&'a Self> {
+        let merchant = merchant_key(&transaction.name);
+        subscriptions
+            .iter()
+            .find(|subscription| merchant_key(&subscription.merchant) == merchant)
+    }
+}
+
+impl fmt::Display for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:<10}  {:>8.2}  {:>7}  {}  {}",
+            self.cadence.to_string(),
+            self.amount,
+            self.charges,
+            self.last_charge.format("%Y-%m-%d"),
+            self.next_charge
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| String::from("-"))
+        )?;
+
+        match self.previous_amount {
+            Some(previous) if self.price_increased() => {
+                write!(f, "  {}", format!("price up from {:.2}", previous).red())
+            }
+            Some(previous) => write!(
+                f,
+                "  {}",
+                format!("price down from {:.2}", previous).green()
+            ),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,339 @@
+use crate::gnosispay::Currency;
+use crate::prelude::*;
+use crate::transactions::{Kind, Transaction, Transactions};
+use chrono::{Datelike, Local, NaiveDate};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs::File;
+use std::io::Write;
+use std::str::FromStr;
+
+/// First day of the fiscal year, as `MM-DD`. A fiscal year is named after the
+/// calendar year it starts in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct YearStart {
+    month: u32,
+    day: u32,
+}
+
+impl Default for YearStart {
+    fn default() -> Self {
+        Self { month: 1, day: 1 }
+    }
+}
+
+impl FromStr for YearStart {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || format!("invalid fiscal year start `{}`, expected `MM-DD`", value);
+        let (month, day) = value.trim().split_once('-').ok_or_else(invalid)?;
+        let month = month.parse().map_err(|_| invalid())?;
+        let day = day.parse().map_err(|_| invalid())?;
+
+        // Must exist every year, so not February 29.
+        if NaiveDate::from_ymd_opt(2023, month, day).is_none() {
+            return Err(format!(
+                "fiscal year start `{}` is not a day of every year",
+                value
+            ));
+        }
+        Ok(Self { month, day })
+    }
+}
+
+impl TryFrom<String> for YearStart {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<YearStart> for String {
+    fn from(start: YearStart) -> Self {
+        start.to_string()
+    }
+}
+
+impl fmt::Display for YearStart {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:02}-{:02}", self.month, self.day)
+    }
+}
+
+impl YearStart {
+    /// `None` when the year is out of the range of dates.
+    fn first_day(&self, year: i32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, self.month, self.day)
+    }
+
+    /// First and last day of a fiscal year.
+    pub fn period(&self, year: i32) -> Result<(NaiveDate, NaiveDate)> {
+        let out_of_range = || format!("fiscal year {} is out of range", year);
+        let first = self.first_day(year).ok_or_else(out_of_range)?;
+        let last = year
+            .checked_add(1)
+            .and_then(|next| self.first_day(next))
+            .and_then(|next| next.pred_opt())
+            .ok_or_else(out_of_range)?;
+        Ok((first, last))
+    }
+
+    /// Fiscal year a day belongs to.
+    pub fn year_of(&self, date: NaiveDate) -> i32 {
+        match self.first_day(date.year()) {
+            Some(first) if date < first => date.year() - 1,
+            _ => date.year(),
+        }
+    }
+}
+
+/// Number and sum of transactions, amounts are positive.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Total {
+    pub count: usize,
+    pub amount: f64,
+}
+
+impl Total {
+    fn add(&mut self, amount: f64) {
+        self.count += 1;
+        self.amount += amount.abs();
+    }
+}
+
+/// Totals by category or by source.
+type Breakdown = BTreeMap<String, Total>;
+
+/// Card purchases paid in another currency than the card's.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ForeignCurrency {
+    /// Numeric ISO 4217 code.
+    pub code: String,
+    pub symbol: String,
+    pub name: String,
+    pub count: usize,
+    /// Amount in the currency paid in.
+    pub amount: f64,
+    /// Amount charged to the card.
+    pub eur: f64,
+}
+
+/// Totals of a fiscal year, for tax filing and accounting.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub year: i32,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub card_spend: Total,
+    /// Card spend per category, split payments by part.
+    pub card_spend_by_category: BTreeMap<String, Total>,
+    /// Card refunds and reversals.
+    pub refunds: Total,
+    /// Funds sent on chain, by the spending allowance or the owners.
+    pub sends: Total,
+    /// EURe burned, e.g. redeemed to a bank account.
+    pub redemptions: Total,
+    pub top_ups: Total,
+    /// Top-ups per source, as named in the transactions.
+    pub top_ups_by_source: BTreeMap<String, Total>,
+    pub foreign_currencies: Vec<ForeignCurrency>,
+}
+
+/// Amount paid in the merchant's currency, `None` for card currency payments.
+fn foreign_amount(transaction: &Transaction) -> Option<(&Currency, f64)> {
+    let details = transaction.details.as_ref()?;
+    if details.transaction_currency.code == details.billing_currency.code {
+        return None;
+    }
+
+    let amount = details.transaction_amount.parse::<f64>().ok()?
+        / 10f64.powi(details.transaction_currency.decimals as i32);
+    Some((&details.transaction_currency, amount))
+}
+
+impl Summary {
+    /// Summary of the transactions of a fiscal year, by local date.
+    pub fn new(transactions: &Transactions, year: i32, start: YearStart) -> Result<Self> {
+        let (from, to) = start.period(year)?;
+        let mut summary = Self {
+            year,
+            from,
+            to,
+            card_spend: Total::default(),
+            card_spend_by_category: BTreeMap::new(),
+            refunds: Total::default(),
+            sends: Total::default(),
+            redemptions: Total::default(),
+            top_ups: Total::default(),
+            top_ups_by_source: BTreeMap::new(),
+            foreign_currencies: Vec::new(),
+        };
+        let mut foreign_currencies: BTreeMap<String, ForeignCurrency> = BTreeMap::new();
+
+        for transaction in transactions.transactions.values() {
+            let date = transaction.date.with_timezone(&Local).date_naive();
+            if date < from || date > to || transaction.amount == 0.0 {
+                continue;
+            }
+
+            match transaction.kind {
+                Kind::Card if transaction.amount < 0.0 => {
+                    summary.card_spend.add(transaction.amount);
+                    for part in transaction.parts() {
+                        let category = part.category.unwrap_or("Uncategorized");
+                        summary
+                            .card_spend_by_category
+                            .entry(category.to_string())
+                            .or_default()
+                            .add(part.amount);
+                    }
+
+                    if let Some((currency, amount)) = foreign_amount(transaction) {
+                        let foreign = foreign_currencies
+                            .entry(currency.code.clone())
+                            .or_insert_with(|| ForeignCurrency {
+                                code: currency.code.clone(),
+                                symbol: currency.symbol.clone(),
+                                name: currency.name.clone(),
+                                ..ForeignCurrency::default()
+                            });
+                        foreign.count += 1;
+                        foreign.amount += amount;
+                        foreign.eur -= transaction.amount;
+                    }
+                }
+                Kind::Card => summary.refunds.add(transaction.amount),
+                Kind::Sent | Kind::Withdrawal => summary.sends.add(transaction.amount),
+                Kind::Burned => summary.redemptions.add(transaction.amount),
+                Kind::Minted | Kind::Deposit => {
+                    summary.top_ups.add(transaction.amount);
+                    summary
+                        .top_ups_by_source
+                        .entry(transaction.name.clone())
+                        .or_default()
+                        .add(transaction.amount);
+                }
+                _ => {}
+            }
+        }
+
+        summary.foreign_currencies = foreign_currencies.into_values().collect();
+        Ok(summary)
+    }
+
+    /// Totals with their breakdown, by category or by source.
+    fn totals(&self) -> [(&'static str, &Total, Option<&Breakdown>); 5] {
+        [
+            (
+                "Card spend",
+                &self.card_spend,
+                Some(&self.card_spend_by_category),
+            ),
+            ("Refunds", &self.refunds, None),
+            ("On-chain sends", &self.sends, None),
+            ("Redemptions", &self.redemptions, None),
+            ("Top-ups", &self.top_ups, Some(&self.top_ups_by_source)),
+        ]
+    }
+
+    /// Export the summary in CSV format.
+    pub fn to_csv(&self, filename: String, delimiter: char) -> Result<()> {
+        let mut file = File::create(filename.clone())?;
+        let d = delimiter;
+
+        writeln!(file, "Item{d}Detail{d}Count{d}Amount{d}Currency{d}EUR")?;
+
+        for (item, total, _) in self.totals() {
+            writeln!(
+                file,
+                "{item}{d}{d}{}{d}{:.2}{d}EUR{d}{:.2}",
+                total.count, total.amount, total.amount
+            )?;
+        }
+        for (item, _, details) in self.totals() {
+            for (detail, total) in details.into_iter().flatten() {
+                writeln!(
+                    file,
+                    "{item}{d}{detail}{d}{}{d}{:.2}{d}EUR{d}{:.2}",
+                    total.count, total.amount, total.amount
+                )?;
+            }
+        }
+        for foreign in &self.foreign_currencies {
+            writeln!(
+                file,
+                "Foreign currency purchases{d}{d}{}{d}{:.2}{d}{}{d}{:.2}",
+                foreign.count, foreign.amount, foreign.name, foreign.eur
+            )?;
+        }
+
+        println!("{}", format!("[+] CSV export to `{}`.", filename).green());
+        Ok(())
+    }
+
+    /// Export the summary in JSON format.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{}",
+            format!("Fiscal year {}: {} to {}", self.year, self.from, self.to).bold()
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{}",
+            format!("{:<30}  {:>5}  {:>10}", "", "Count", "EUR").blue()
+        )?;
+
+        for (item, total, details) in self.totals() {
+            writeln!(
+                f,
+                "{:<30}  {:>5}  {:>10.2}",
+                item, total.count, total.amount
+            )?;
+            for (detail, total) in details.into_iter().flatten() {
+                writeln!(
+                    f,
+                    "  {:<28}  {:>5}  {:>10.2}",
+                    detail, total.count, total.amount
+                )?;
+            }
+        }
+
+        if !self.foreign_currencies.is_empty() {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{}",
+                format!(
+                    "{:<30}  {:>5}  {:>10}  {:>10}",
+                    "Foreign currency purchases", "Count", "Amount", "EUR"
+                )
+                .blue()
+            )?;
+            for foreign in &self.foreign_currencies {
+                writeln!(
+                    f,
+                    "{:<30}  {:>5}  {:>10.2}  {:>10.2}",
+                    format!("{} ({})", foreign.name, foreign.symbol),
+                    foreign.count,
+                    foreign.amount,
+                    foreign.eur
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
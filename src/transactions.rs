@@ -1,28 +1,293 @@
-use crate::gnosispay::GnosisTransaction;
-use crate::gnosisscan::Response;
+use crate::address_book::AddressBook;
+use crate::annotations::{Annotation, Annotations};
+use crate::filter::Filter;
+use crate::gnosispay::{Currency, GnosisTransaction};
+use crate::gnosisscan::{LogsResponse, Response};
+use crate::mcc::{self, Overrides};
 use crate::monerium::GNOSIS_BANK;
 use crate::prelude::*;
-use alloy::primitives::{Address, U256, utils::format_units};
+use crate::safe::{
+    EXECUTION_FAILURE, EXECUTION_FROM_MODULE_FAILURE, EXECUTION_FROM_MODULE_SUCCESS,
+    EXECUTION_SUCCESS,
+};
+use alloy::primitives::{Address, B256, U256, utils::format_units};
+use chrono::format::{Item, StrftimeItems};
 use chrono::{DateTime, Utc};
 use colored::Colorize;
+use serde::Serialize;
 use std::fmt;
 use std::fs::File;
 use std::io::Write;
 use std::{collections::HashMap, str::FromStr};
 
-#[derive(Debug, Clone)]
+/// What moved the funds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    #[serde(rename = "mint")]
+    Minted,
+    #[serde(rename = "burn")]
+    Burned,
+    /// Funds received on chain.
+    Deposit,
+    /// Card payment, through the spending allowance.
+    Card,
+    /// Funds sent on chain.
+    Sent,
+    /// Funds sent by a Safe transaction signed by the owners.
+    Withdrawal,
+    /// Safe transaction signed by the owners, without EURe transfer.
+    #[serde(rename = "safe")]
+    SafeTransaction,
+    /// Safe transaction that reverted.
+    SafeFailure,
+    Unknown,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 9] = [
+        Kind::Minted,
+        Kind::Burned,
+        Kind::Deposit,
+        Kind::Card,
+        Kind::Sent,
+        Kind::Withdrawal,
+        Kind::SafeTransaction,
+        Kind::SafeFailure,
+        Kind::Unknown,
+    ];
+
+    /// Name in filters and JSON, e.g. `mint` or `safe-failure`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Kind::Minted => "mint",
+            Kind::Burned => "burn",
+            Kind::Deposit => "deposit",
+            Kind::Card => "card",
+            Kind::Sent => "sent",
+            Kind::Withdrawal => "withdrawal",
+            Kind::SafeTransaction => "safe",
+            Kind::SafeFailure => "safe-failure",
+            Kind::Unknown => "unknown",
+        }
+    }
+}
+
+/// Parse the name of a kind, case insensitive.
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        Kind::ALL
+            .into_iter()
+            .find(|kind| kind.name().eq_ignore_ascii_case(value.trim()))
+            .ok_or_else(|| {
+                let names: Vec<_> = Kind::ALL.iter().map(Kind::name).collect();
+                format!(
+                    "invalid kind `{}`, expected one of {}",
+                    value,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kind = match self {
+            Kind::Minted => "mint",
+            Kind::Burned => "burn",
+            Kind::Deposit => "deposit",
+            Kind::Card => "card",
+            Kind::Sent => "sent",
+            Kind::Withdrawal => "owner withdrawal",
+            Kind::SafeTransaction => "safe transaction",
+            Kind::SafeFailure => "failed safe transaction",
+            Kind::Unknown => "unknown",
+        };
+        write!(f, "{}", kind)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct Transaction {
     pub hash: String,
     pub date: DateTime<Utc>,
     pub amount: f64,
     pub name: String,
     pub description: String,
+    pub kind: Kind,
+    /// Merchant category code of card payments.
+    pub mcc: Option<String>,
+    /// Merchant country of card payments.
+    pub country: Option<String>,
+    /// Spending category, from the MCC table or the user overrides.
+    pub category: Option<String>,
+    /// Gnosis Pay record of card payments.
+    pub details: Option<GnosisTransaction>,
+    /// Tags, note and receipts added by the user.
+    #[serde(skip_serializing_if = "Annotation::is_empty")]
+    pub annotation: Annotation,
+}
+
+impl Transaction {
+    /// Card payment as seen by Gnosis Pay, before the on-chain settlement is
+    /// imported. `None` when it was never settled.
+    pub fn from_gnosispay(gnosis_transaction: &GnosisTransaction) -> Result<Option<Self>> {
+        let Some(settlement) = gnosis_transaction.transactions.first() else {
+            return Ok(None);
+        };
+
+        let date = gnosis_transaction
+            .cleared_at
+            .as_deref()
+            .unwrap_or(&gnosis_transaction.created_at);
+        let date = DateTime::parse_from_rfc3339(date)?.with_timezone(&Utc);
+
+        // Amounts are in the smallest unit of the billing currency.
+        let amount = gnosis_transaction.billing_amount.parse::<f64>()?
+            / 10f64.powi(gnosis_transaction.billing_currency.decimals as i32);
+        let amount = match gnosis_transaction.kind.as_str() {
+            "Refund" | "Reversal" => amount,
+            _ => -amount,
+        };
+
+        Ok(Some(Self {
+            hash: settlement.hash.clone(),
+            date,
+            amount,
+            name: gnosis_transaction.merchant.name.trim().to_string(),
+            description: format!(
+                "{} - {}",
+                gnosis_transaction.merchant.city.trim(),
+                gnosis_transaction.merchant.country.name.trim(),
+            ),
+            kind: Kind::Card,
+            mcc: Some(gnosis_transaction.mcc.trim().to_string()),
+            country: Some(gnosis_transaction.merchant.country.name.trim().to_string()),
+            category: Some(mcc::category(&gnosis_transaction.mcc).to_string()),
+            details: Some(gnosis_transaction.clone()),
+            annotation: Annotation::default(),
+        }))
+    }
+
+    /// Currency the card payment was made in, from the Gnosis Pay details.
+    pub fn currency(&self) -> Option<&Currency> {
+        self.details
+            .as_ref()
+            .map(|details| &details.transaction_currency)
+    }
+
+    /// Apply the user category, by merchant name first, then by MCC.
+    pub fn categorize(&mut self, overrides: &Overrides) {
+        if let Some(category) = overrides.category(&self.name, self.mcc.as_deref()) {
+            self.category = Some(category.clone());
+        }
+    }
+
+    /// Parts of the transaction, one per split, or the whole transaction
+    /// when it is not split.
+    pub fn parts(&self) -> Vec<Part<'_>> {
+        if self.annotation.splits.is_empty() {
+            return vec![Part {
+                amount: self.amount,
+                category: self.category.as_deref(),
+                note: None,
+            }];
+        }
+
+        self.annotation
+            .splits
+            .iter()
+            .map(|split| Part {
+                amount: split.amount.copysign(self.amount),
+                category: Some(&split.category),
+                note: split.note.as_deref(),
+            })
+            .collect()
+    }
+}
+
+/// Share of a transaction in a category.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Part<'a> {
+    /// Signed like the amount of the transaction.
+    pub amount: f64,
+    pub category: Option<&'a str>,
+    /// Note of the split.
+    pub note: Option<&'a str>,
+}
+
+/// Date and time of OFX statements, in UTC.
+const OFX_DATE_FORMAT: &str = "%Y%m%d%H%M%S";
+
+/// Account of the wallet in ledger exports.
+const LEDGER_ACCOUNT: &str = "Assets:Gnosis Pay";
+
+/// Description of a transaction with the note of the user.
+fn memo(transaction: &Transaction) -> String {
+    match &transaction.annotation.note {
+        Some(note) if transaction.description.is_empty() => note.clone(),
+        Some(note) => format!("{} - {}", transaction.description, note),
+        None => transaction.description.clone(),
+    }
+}
+
+/// QIF, OFX and ledger fields are one line each.
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], " ")
+}
+
+/// Escape the characters of OFX markup.
+fn ofx_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Quote a CSV field when it contains the delimiter, a quote or a new line.
+fn csv_field(value: &str, delimiter: char) -> String {
+    if value.contains([delimiter, '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// CSV export options.
+#[derive(Debug, Clone)]
+pub struct CsvOptions {
+    pub delimiter: char,
+    /// chrono format of the date column.
+    pub date_format: String,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ';',
+            date_format: String::from("%d-%m-%Y"),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Check a chrono date format, formatting with a bad one panics.
+    pub fn check_date_format(format: &str) -> Result<()> {
+        if StrftimeItems::new(format).any(|item| item == Item::Error) {
+            return Err(format!("invalid date format `{}`", format).into());
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Transactions {
     pub wallet_address: Address,
     pub transactions: HashMap<String, Transaction>,
+    /// Labels of the counterparties of on-chain transfers.
+    pub address_book: AddressBook,
 }
 
 impl Transactions {
@@ -33,9 +298,17 @@ impl Transactions {
         Ok(Self {
             wallet_address,
             transactions,
+            address_book: AddressBook::default(),
         })
     }
 
+    /// Name transfers after the labels of the user, well-known contracts are
+    /// always named.
+    pub fn with_address_book(mut self, address_book: AddressBook) -> Self {
+        self.address_book = address_book;
+        self
+    }
+
     /// Import transactions from Gnosis Scan.
     pub fn import_from_gnosisscan(&mut self, response: Response) -> Result<()> {
         if response.result.is_none() {
@@ -57,29 +330,34 @@ impl Transactions {
             let amount = format_units(amount, token_tx.token_decimal)?;
             let mut amount = amount.parse::<f64>()?;
 
-            // Parse description.
-            let name = if Address::from_str(token_tx.from.as_str())? == Address::ZERO {
-                String::from("Minted on chain")
-            } else if Address::from_str(token_tx.to.as_str())? == Address::ZERO {
+            // Parse description, naming the counterparty when it is known.
+            let from = Address::from_str(token_tx.from.as_str())?;
+            let to = Address::from_str(token_tx.to.as_str())?;
+            let (name, description, kind) = if from == Address::ZERO {
+                (String::from("Minted on chain"), String::new(), Kind::Minted)
+            } else if to == Address::ZERO {
                 amount = -amount;
-                String::from("Burned on chain")
-            } else if Address::from_str(token_tx.to.as_str())? == self.wallet_address {
-                String::from("Added funds on chain")
-            } else if Address::from_str(token_tx.from.as_str())? == self.wallet_address
-                && Address::from_str(token_tx.to.as_str())? != monerium_address
-            {
+                (String::from("Burned on chain"), String::new(), Kind::Burned)
+            } else if to == self.wallet_address {
+                let name = match self.address_book.label(from) {
+                    Some(label) => format!("Added funds from {}", label),
+                    None => String::from("Added funds on chain"),
+                };
+                (name, format!("From {}", from), Kind::Deposit)
+            } else if from == self.wallet_address && to != monerium_address {
                 amount = -amount;
-                String::from("Sent funds on chain")
-            } else if Address::from_str(token_tx.from.as_str())? == self.wallet_address
-                && Address::from_str(token_tx.to.as_str())? == monerium_address
-            {
+                let name = match self.address_book.label(to) {
+                    Some(label) => format!("Sent to {}", label),
+                    None => String::from("Sent funds on chain"),
+                };
+                (name, format!("To {}", to), Kind::Sent)
+            } else if from == self.wallet_address && to == monerium_address {
                 amount = -amount;
                 // This will actually be updated when importing transactions from GnosisPay.
-                String::from("From Gnosiscard")
+                (String::from("From Gnosiscard"), String::new(), Kind::Card)
             } else {
-                String::from("")
+                (String::from(""), String::new(), Kind::Unknown)
             };
-            let description = String::from("");
 
             let transaction = Transaction {
                 hash: token_tx.hash.clone(),
@@ -87,6 +365,12 @@ impl Transactions {
                 amount,
                 name,
                 description,
+                kind,
+                mcc: None,
+                country: None,
+                category: None,
+                details: None,
+                annotation: Annotation::default(),
             };
 
             self.transactions.insert(token_tx.hash.clone(), transaction);
@@ -95,17 +379,97 @@ impl Transactions {
         Ok(())
     }
 
+    /// Import Safe transactions executed by the owners or their modules, so
+    /// withdrawals are told apart from card spending. Call after `import_from_gnosisscan`.
+    pub fn import_safe_executions(&mut self, response: LogsResponse) -> Result<()> {
+        let Some(logs) = response.result else {
+            return Err("invalid response".into());
+        };
+
+        for log in logs {
+            let Some(topic) = log.topics.first() else {
+                continue;
+            };
+            let topic = B256::from_str(topic)?;
+            let (success, description) = match topic {
+                // The Safe transaction hash is the first word of the data.
+                EXECUTION_SUCCESS | EXECUTION_FAILURE => {
+                    let safe_tx_hash = log.data.get(..66).unwrap_or(&log.data);
+                    (
+                        topic == EXECUTION_SUCCESS,
+                        format!("Safe transaction {}", safe_tx_hash),
+                    )
+                }
+                // The module is the indexed topic, e.g. the Delay queue.
+                EXECUTION_FROM_MODULE_SUCCESS | EXECUTION_FROM_MODULE_FAILURE => {
+                    let module = log
+                        .topics
+                        .get(1)
+                        .and_then(|module| module.get(module.len().saturating_sub(40)..))
+                        .unwrap_or_default();
+                    (
+                        topic == EXECUTION_FROM_MODULE_SUCCESS,
+                        format!("Executed by module 0x{}", module),
+                    )
+                }
+                _ => continue,
+            };
+
+            match self.transactions.get_mut(&log.transaction_hash) {
+                // EURe sent by the owners.
+                Some(transaction) if success && transaction.kind == Kind::Sent => {
+                    transaction.name = String::from("Withdrawn by owner");
+                    transaction.description = description;
+                    transaction.kind = Kind::Withdrawal;
+                }
+                Some(_) => {}
+                None => {
+                    let timestamp =
+                        i64::from_str_radix(log.timestamp.trim_start_matches("0x"), 16)?;
+                    let Some(date) = DateTime::from_timestamp(timestamp, 0) else {
+                        return Err("cannot parse date".into());
+                    };
+
+                    let (name, kind) = if success {
+                        ("Safe transaction", Kind::SafeTransaction)
+                    } else {
+                        ("Failed Safe transaction", Kind::SafeFailure)
+                    };
+
+                    self.transactions.insert(
+                        log.transaction_hash.clone(),
+                        Transaction {
+                            hash: log.transaction_hash,
+                            date,
+                            amount: 0.0,
+                            name: String::from(name),
+                            description,
+                            kind,
+                            mcc: None,
+                            country: None,
+                            category: None,
+                            details: None,
+                            annotation: Annotation::default(),
+                        },
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     /// Merge description with information from Gnosis Pay.
     pub fn merge_description_from_gnosispay(
         &mut self,
         gnonis_transactions: Vec<GnosisTransaction>,
     ) -> Result<()> {
         for gnonis_transaction in gnonis_transactions {
-            if gnonis_transaction.transactions.len() > 0 {
+            if !gnonis_transaction.transactions.is_empty() {
                 let hash = gnonis_transaction.transactions[0].hash.clone();
 
                 if self.transactions.contains_key(&hash) {
-                    let name = format!("{}", gnonis_transaction.merchant.name.trim());
+                    let name = gnonis_transaction.merchant.name.trim().to_string();
 
                     // Use the merchant's city for the description.
                     let description = format!(
@@ -117,6 +481,12 @@ impl Transactions {
                     let transaction = self.transactions.get_mut(&hash).unwrap();
                     transaction.name = name;
                     transaction.description = description;
+                    transaction.kind = Kind::Card;
+                    transaction.mcc = Some(gnonis_transaction.mcc.trim().to_string());
+                    transaction.category = Some(mcc::category(&gnonis_transaction.mcc).to_string());
+                    transaction.country =
+                        Some(gnonis_transaction.merchant.country.name.trim().to_string());
+                    transaction.details = Some(gnonis_transaction);
                 }
             }
         }
@@ -124,44 +494,292 @@ impl Transactions {
         Ok(())
     }
 
-    /// Check that the sum of all transaction match current balance.
-    pub fn verify(&self) {
-        let mut amount = 0.0;
+    /// Apply the user categories, by merchant name first, then by MCC.
+    pub fn categorize(&mut self, overrides: &Overrides) {
+        for transaction in self.transactions.values_mut() {
+            transaction.categorize(overrides);
+        }
+    }
 
-        for (_, transaction) in &self.transactions {
-            amount = amount + transaction.amount;
+    /// Attach the user annotations to their transactions.
+    pub fn annotate(&mut self, annotations: &Annotations) {
+        for transaction in self.transactions.values_mut() {
+            transaction.annotation = annotations
+                .get(&transaction.hash)
+                .cloned()
+                .unwrap_or_default();
         }
+    }
 
-        println!("Total: {:.2}", amount);
+    /// Keep only the transactions matching the filter.
+    pub fn retain(&mut self, filter: &Filter) {
+        self.transactions
+            .retain(|_, transaction| filter.matches(transaction));
+    }
+
+    /// Sum of all transactions, which should match the current balance.
+    pub fn total(&self) -> f64 {
+        self.transactions.values().map(|t| t.amount).sum()
+    }
+
+    /// Check that the sum of all transaction match current balance.
+    pub fn verify(&self) {
+        println!("Total: {:.2}", self.total());
     }
 
     /// Export all transactions in CSV format.
-    pub fn to_csv(&self, filename: String) -> Result<()> {
+    pub fn to_csv(&self, filename: String, options: &CsvOptions) -> Result<()> {
+        CsvOptions::check_date_format(&options.date_format)?;
         let mut file = File::create(filename.clone())?;
+        let d = options.delimiter;
 
-        file.write("ID;Date;Amount;Name;Description\n".as_bytes())?;
+        file.write_all(
+            format!(
+                "ID{d}Date{d}Amount{d}Name{d}Description{d}Category{d}Tags{d}Note{d}Receipts\n"
+            )
+            .as_bytes(),
+        )?;
 
         // Sorting transactions by date.
         let mut sorted: Vec<_> = self.transactions.iter().collect();
         sorted.sort_by_key(|a| a.1.date);
 
         for (_, transaction) in sorted {
-            file.write(
-                format!(
-                    "{};{};{:.2};{};{}\n",
-                    transaction.hash,
-                    transaction.date.format("%d-%m-%Y"),
-                    transaction.amount,
-                    transaction.name,
-                    transaction.description
-                )
-                .as_bytes(),
-            )?;
+            let annotation = &transaction.annotation;
+            let receipts: Vec<_> = annotation
+                .receipts
+                .iter()
+                .map(|receipt| receipt.display().to_string())
+                .collect();
+
+            // A split transaction is a line per part, with the same ID.
+            for part in transaction.parts() {
+                // The note of the part follows the note of the transaction.
+                let note = match (annotation.note.as_deref(), part.note) {
+                    (Some(note), Some(part_note)) => format!("{} - {}", note, part_note),
+                    (note, part_note) => part_note.or(note).unwrap_or_default().to_string(),
+                };
+                file.write_all(
+                    format!(
+                        "{}{d}{}{d}{:.2}{d}{}{d}{}{d}{}{d}{}{d}{}{d}{}\n",
+                        transaction.hash,
+                        transaction.date.format(&options.date_format),
+                        part.amount,
+                        csv_field(&transaction.name, d),
+                        csv_field(&transaction.description, d),
+                        csv_field(part.category.unwrap_or_default(), d),
+                        csv_field(&annotation.tags.join(", "), d),
+                        csv_field(&note, d),
+                        csv_field(&receipts.join(", "), d),
+                    )
+                    .as_bytes(),
+                )?;
+            }
         }
 
         println!("{}", format!("[+] CSV export to `{}`.", filename).green());
         Ok(())
     }
+
+    /// Transactions moving money sorted by date, Safe transactions without
+    /// transfer are left out.
+    fn transfers_by_date(&self) -> Vec<&Transaction> {
+        let mut sorted: Vec<_> = self
+            .transactions
+            .values()
+            .filter(|transaction| transaction.amount != 0.0)
+            .collect();
+        sorted.sort_by_key(|transaction| transaction.date);
+        sorted
+    }
+
+    /// Export all transactions in QIF format, for GnuCash, Quicken and
+    /// most personal finance tools.
+    pub fn to_qif(&self, filename: String) -> Result<()> {
+        let mut file = File::create(filename.clone())?;
+
+        writeln!(file, "!Type:Bank")?;
+        for transaction in self.transfers_by_date() {
+            writeln!(file, "D{}", transaction.date.format("%m/%d/%Y"))?;
+            writeln!(file, "T{:.2}", transaction.amount)?;
+            writeln!(file, "N{}", transaction.hash)?;
+            writeln!(file, "P{}", single_line(&transaction.name))?;
+            writeln!(file, "M{}", single_line(&memo(transaction)))?;
+
+            // A split transaction is a split line per part.
+            match transaction.annotation.splits.is_empty() {
+                true => {
+                    if let Some(category) = &transaction.category {
+                        writeln!(file, "L{}", single_line(category))?;
+                    }
+                }
+                false => {
+                    for part in transaction.parts() {
+                        writeln!(file, "S{}", single_line(part.category.unwrap_or_default()))?;
+                        if let Some(note) = part.note {
+                            writeln!(file, "E{}", single_line(note))?;
+                        }
+                        writeln!(file, "${:.2}", part.amount)?;
+                    }
+                }
+            }
+            writeln!(file, "^")?;
+        }
+
+        println!("{}", format!("[+] QIF export to `{}`.", filename).green());
+        Ok(())
+    }
+
+    /// Export all transactions as an OFX bank statement. OFX has no split
+    /// transactions, so a split transaction is a statement line per part.
+    pub fn to_ofx(&self, filename: String) -> Result<()> {
+        let mut file = File::create(filename.clone())?;
+        let now = Utc::now().format(OFX_DATE_FORMAT);
+        let sorted = self.transfers_by_date();
+
+        writeln!(
+            file,
+            r#"<?xml version="1.0" encoding="UTF-8" standalone="no"?>"#
+        )?;
+        writeln!(
+            file,
+            r#"<?OFX OFXHEADER="200" VERSION="220" SECURITY="NONE" OLDFILEUID="NONE" NEWFILEUID="NONE"?>"#
+        )?;
+        writeln!(file, "<OFX>")?;
+        writeln!(file, "<SIGNONMSGSRSV1><SONRS>")?;
+        writeln!(
+            file,
+            "<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>"
+        )?;
+        writeln!(file, "<DTSERVER>{}</DTSERVER><LANGUAGE>ENG</LANGUAGE>", now)?;
+        writeln!(file, "</SONRS></SIGNONMSGSRSV1>")?;
+        writeln!(file, "<BANKMSGSRSV1><STMTTRNRS>")?;
+        writeln!(file, "<TRNUID>0</TRNUID>")?;
+        writeln!(
+            file,
+            "<STATUS><CODE>0</CODE><SEVERITY>INFO</SEVERITY></STATUS>"
+        )?;
+        writeln!(file, "<STMTRS>")?;
+        writeln!(file, "<CURDEF>EUR</CURDEF>")?;
+        writeln!(
+            file,
+            "<BANKACCTFROM><BANKID>GNOSISPAY</BANKID><ACCTID>{}</ACCTID><ACCTTYPE>CHECKING</ACCTTYPE></BANKACCTFROM>",
+            self.wallet_address
+        )?;
+        writeln!(file, "<BANKTRANLIST>")?;
+        if let (Some(first), Some(last)) = (sorted.first(), sorted.last()) {
+            writeln!(
+                file,
+                "<DTSTART>{}</DTSTART><DTEND>{}</DTEND>",
+                first.date.format(OFX_DATE_FORMAT),
+                last.date.format(OFX_DATE_FORMAT)
+            )?;
+        }
+
+        for transaction in sorted {
+            let parts = transaction.parts();
+            for (index, part) in parts.iter().enumerate() {
+                // Identifiers must be unique, parts are numbered from 1.
+                let id = match parts.len() {
+                    1 => transaction.hash.clone(),
+                    _ => format!("{}-{}", transaction.hash, index + 1),
+                };
+                let memo = match (parts.len(), part.category, part.note) {
+                    (1, _, _) => memo(transaction),
+                    (_, Some(category), Some(note)) => format!("{}: {}", category, note),
+                    (_, category, _) => category.unwrap_or_default().to_string(),
+                };
+
+                writeln!(file, "<STMTTRN>")?;
+                writeln!(
+                    file,
+                    "<TRNTYPE>{}</TRNTYPE>",
+                    if part.amount < 0.0 { "DEBIT" } else { "CREDIT" }
+                )?;
+                writeln!(
+                    file,
+                    "<DTPOSTED>{}</DTPOSTED>",
+                    transaction.date.format(OFX_DATE_FORMAT)
+                )?;
+                writeln!(file, "<TRNAMT>{:.2}</TRNAMT>", part.amount)?;
+                writeln!(file, "<FITID>{}</FITID>", id)?;
+                // Names are limited to 32 characters.
+                let name: String = single_line(&transaction.name).chars().take(32).collect();
+                writeln!(file, "<NAME>{}</NAME>", ofx_text(&name))?;
+                writeln!(file, "<MEMO>{}</MEMO>", ofx_text(&single_line(&memo)))?;
+                writeln!(file, "</STMTTRN>")?;
+            }
+        }
+
+        writeln!(file, "</BANKTRANLIST>")?;
+        writeln!(
+            file,
+            "<LEDGERBAL><BALAMT>{:.2}</BALAMT><DTASOF>{}</DTASOF></LEDGERBAL>",
+            self.total(),
+            now
+        )?;
+        writeln!(file, "</STMTRS>")?;
+        writeln!(file, "</STMTTRNRS></BANKMSGSRSV1>")?;
+        writeln!(file, "</OFX>")?;
+
+        println!("{}", format!("[+] OFX export to `{}`.", filename).green());
+        Ok(())
+    }
+
+    /// Export all transactions as a ledger journal, also read by hledger.
+    /// Categories are expense accounts, with a posting per part of a split
+    /// transaction.
+    pub fn to_ledger(&self, filename: String) -> Result<()> {
+        let mut file = File::create(filename.clone())?;
+
+        for transaction in self.transfers_by_date() {
+            let annotation = &transaction.annotation;
+            writeln!(
+                file,
+                "{} {}  ; {}",
+                transaction.date.format("%Y/%m/%d"),
+                single_line(&transaction.name),
+                transaction.hash
+            )?;
+            if !transaction.description.is_empty() {
+                writeln!(file, "    ; {}", single_line(&transaction.description))?;
+            }
+            if let Some(note) = &annotation.note {
+                writeln!(file, "    ; {}", single_line(note))?;
+            }
+            if !annotation.tags.is_empty() {
+                writeln!(file, "    ; :{}:", annotation.tags.join(":"))?;
+            }
+
+            for part in transaction.parts() {
+                let root = if part.amount < 0.0 {
+                    "Expenses"
+                } else {
+                    "Income"
+                };
+                let account = format!("{}:{}", root, part.category.unwrap_or("Uncategorized"));
+                write!(
+                    file,
+                    "    {:<40}  {:>10.2} EUR",
+                    single_line(&account),
+                    -part.amount
+                )?;
+                match part.note {
+                    Some(note) => writeln!(file, "  ; {}", single_line(note))?,
+                    None => writeln!(file)?,
+                }
+            }
+            // Left for ledger to balance, parts may be a fraction of a cent off.
+            writeln!(file, "    {}", LEDGER_ACCOUNT)?;
+            writeln!(file)?;
+        }
+
+        println!(
+            "{}",
+            format!("[+] Ledger export to `{}`.", filename).green()
+        );
+        Ok(())
+    }
 }
 
 impl fmt::Display for Transactions {
@@ -173,33 +791,47 @@ impl fmt::Display for Transactions {
 
         // Display all transactions.
         for (_, transaction) in sorted {
-            write!(f, "{}: {}\n", "Hash".blue(), transaction.hash.yellow())?;
-            write!(
+            writeln!(f, "{}: {}", "Hash".blue(), transaction.hash.yellow())?;
+            writeln!(
                 f,
-                "{}: {}\n",
+                "{}: {}",
                 "Date".blue(),
-                transaction.date.format("%d-%m-%Y").to_string()
+                transaction.date.format("%d-%m-%Y")
             )?;
 
             if transaction.amount > 0.0 {
-                write!(
+                writeln!(
                     f,
-                    "{}: {}\n",
+                    "{}: {}",
                     "Amount".blue(),
                     format!("{:.2}", transaction.amount).green()
                 )?;
             } else {
-                write!(
+                writeln!(
                     f,
-                    "{}: {}\n",
+                    "{}: {}",
                     "Amount".blue(),
                     format!("{:.2}", transaction.amount).red()
                 )?;
             }
 
-            write!(f, "{}: {}\n", "Name".blue(), transaction.name)?;
-            write!(f, "{}: {}\n", "Description".blue(), transaction.description)?;
-            write!(f, "\n")?;
+            writeln!(f, "{}: {}", "Kind".blue(), transaction.kind)?;
+            writeln!(f, "{}: {}", "Name".blue(), transaction.name)?;
+            writeln!(f, "{}: {}", "Description".blue(), transaction.description)?;
+            if let Some(category) = &transaction.category {
+                writeln!(f, "{}: {}", "Category".blue(), category)?;
+            }
+            if let Some(code) = &transaction.mcc {
+                writeln!(
+                    f,
+                    "{}: {} {}",
+                    "MCC".blue(),
+                    code,
+                    mcc::description(code).unwrap_or_default()
+                )?;
+            }
+            write!(f, "{}", transaction.annotation)?;
+            writeln!(f)?;
         }
         Ok(())
     }
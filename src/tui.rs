@@ -0,0 +1,456 @@
+use gnosispay::annotations::Annotations;
+use gnosispay::gnosispay::{Currency, GnosisTransaction};
+use gnosispay::mcc;
+use gnosispay::prelude::*;
+use gnosispay::transactions::{Transaction, Transactions};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Paragraph, Row, Table, TableState, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::cmp::Ordering;
+
+/// Rows skipped by page up and page down.
+const PAGE: usize = 20;
+
+/// Sortable columns, selected with their number key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Column {
+    Date,
+    Amount,
+    Kind,
+    Name,
+    Category,
+}
+
+impl Column {
+    const ALL: [Column; 5] = [
+        Column::Date,
+        Column::Amount,
+        Column::Kind,
+        Column::Name,
+        Column::Category,
+    ];
+
+    fn title(&self) -> &'static str {
+        match self {
+            Column::Date => "Date",
+            Column::Amount => "Amount",
+            Column::Kind => "Kind",
+            Column::Name => "Name",
+            Column::Category => "Category",
+        }
+    }
+
+    fn compare(&self, a: &Transaction, b: &Transaction) -> Ordering {
+        match self {
+            Column::Date => a.date.cmp(&b.date),
+            Column::Amount => a.amount.total_cmp(&b.amount),
+            Column::Kind => a.kind.to_string().cmp(&b.kind.to_string()),
+            Column::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            Column::Category => a.category.cmp(&b.category),
+        }
+    }
+}
+
+/// Transactions browser state.
+struct App {
+    transactions: Vec<Transaction>,
+    annotations: Annotations,
+    /// Indices of the transactions shown, filtered and sorted.
+    rows: Vec<usize>,
+    state: TableState,
+    sort: Column,
+    ascending: bool,
+    filter: String,
+    /// Typing in the filter.
+    editing: bool,
+    details: bool,
+    /// Error shown in the status bar.
+    status: Option<String>,
+}
+
+/// Browse transactions in the terminal.
+pub fn run(transactions: Transactions, annotations: Annotations) -> Result<()> {
+    let mut app = App {
+        transactions: transactions.transactions.into_values().collect(),
+        annotations,
+        rows: Vec::new(),
+        state: TableState::default(),
+        sort: Column::Date,
+        ascending: false,
+        filter: String::new(),
+        editing: false,
+        details: false,
+        status: None,
+    };
+    app.refresh();
+
+    // Restore the terminal even when the loop fails.
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+/// Text searched by the filter.
+fn haystack(transaction: &Transaction) -> String {
+    [
+        transaction.hash.as_str(),
+        transaction.name.as_str(),
+        transaction.description.as_str(),
+        transaction.category.as_deref().unwrap_or_default(),
+        transaction.mcc.as_deref().unwrap_or_default(),
+        transaction.country.as_deref().unwrap_or_default(),
+        &transaction.kind.to_string(),
+        &format!("{:.2}", transaction.amount),
+    ]
+    .join(" ")
+    .to_lowercase()
+}
+
+/// Amount in the smallest unit of a currency, as a decimal number.
+fn to_amount(value: &str, currency: &Currency) -> Option<f64> {
+    let value = value.parse::<f64>().ok()?;
+    Some(value / 10f64.powi(currency.decimals as i32))
+}
+
+impl App {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()?
+                && key.kind == KeyEventKind::Press
+                && self.handle_key(key)
+            {
+                return Ok(());
+            }
+        }
+    }
+
+    fn selected(&self) -> Option<&Transaction> {
+        let index = self.rows.get(self.state.selected()?)?;
+        self.transactions.get(*index)
+    }
+
+    /// Filter and sort the rows, keeping the selected transaction.
+    fn refresh(&mut self) {
+        let selected = self.selected().map(|transaction| transaction.hash.clone());
+        let filter = self.filter.to_lowercase();
+
+        let mut rows: Vec<usize> = (0..self.transactions.len())
+            .filter(|index| haystack(&self.transactions[*index]).contains(&filter))
+            .collect();
+        rows.sort_by(|a, b| {
+            let ordering = self
+                .sort
+                .compare(&self.transactions[*a], &self.transactions[*b]);
+            if self.ascending {
+                ordering
+            } else {
+                ordering.reverse()
+            }
+        });
+        self.rows = rows;
+
+        let position = selected.and_then(|hash| {
+            self.rows
+                .iter()
+                .position(|index| self.transactions[*index].hash == hash)
+        });
+        self.state.select(match position {
+            Some(position) => Some(position),
+            None if self.rows.is_empty() => None,
+            None => Some(0),
+        });
+    }
+
+    fn move_by(&mut self, offset: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let selected = self.state.selected().unwrap_or_default() as isize;
+        let last = self.rows.len() as isize - 1;
+        self.state
+            .select(Some((selected + offset).clamp(0, last) as usize));
+    }
+
+    fn sort_by(&mut self, column: Column) {
+        if self.sort == column {
+            self.ascending = !self.ascending;
+        } else {
+            self.sort = column;
+            self.ascending = true;
+        }
+        self.refresh();
+    }
+
+    fn toggle_reviewed(&mut self) {
+        let Some(hash) = self.selected().map(|transaction| transaction.hash.clone()) else {
+            return;
+        };
+
+        // Other commands may have annotated since the table was opened.
+        if let Err(err) = self.annotations.reload() {
+            self.status = Some(format!("Cannot read annotations: {}", err));
+            return;
+        }
+
+        let annotation = self.annotations.entry(&hash);
+        annotation.reviewed = !annotation.reviewed;
+        self.status = self
+            .annotations
+            .save()
+            .err()
+            .map(|err| format!("Cannot save annotations: {}", err));
+    }
+
+    /// Handle a key press, `true` to quit.
+    fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if self.editing {
+            match key.code {
+                KeyCode::Enter => self.editing = false,
+                KeyCode::Esc => {
+                    self.editing = false;
+                    self.filter.clear();
+                }
+                KeyCode::Backspace => {
+                    self.filter.pop();
+                }
+                KeyCode::Char(c) => self.filter.push(c),
+                _ => return false,
+            }
+            self.refresh();
+            return false;
+        }
+
+        match key.code {
+            KeyCode::Char('q') => return true,
+            KeyCode::Esc if self.details => self.details = false,
+            KeyCode::Esc => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(PAGE as isize),
+            KeyCode::PageUp => self.move_by(-(PAGE as isize)),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(isize::MIN / 2),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX / 2),
+            KeyCode::Enter => self.details = !self.details,
+            KeyCode::Char('/') => self.editing = true,
+            KeyCode::Char(' ') | KeyCode::Char('r') => self.toggle_reviewed(),
+            KeyCode::Char(c @ '1'..='5') => {
+                self.sort_by(Column::ALL[c as usize - '1' as usize]);
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+
+        if self.details {
+            let [table, details] =
+                Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)])
+                    .areas(main);
+            self.draw_table(frame, table);
+            self.draw_details(frame, details);
+        } else {
+            self.draw_table(frame, main);
+        }
+
+        let line = if self.editing {
+            Line::from(format!("/{}", self.filter)).yellow()
+        } else if let Some(status) = &self.status {
+            Line::from(status.as_str()).red()
+        } else {
+            let filter = if self.filter.is_empty() {
+                String::new()
+            } else {
+                format!("filter: {}  ", self.filter)
+            };
+            Line::from(format!(
+                "{}↑↓ move  1-5 sort  / filter  enter details  space reviewed  q quit",
+                filter
+            ))
+            .dark_gray()
+        };
+        frame.render_widget(line, status);
+    }
+
+    fn draw_table(&mut self, frame: &mut Frame, area: Rect) {
+        let header = Row::new(std::iter::once(Cell::from("✓")).chain(
+            Column::ALL.iter().enumerate().map(|(i, column)| {
+                let arrow = match (self.sort == *column, self.ascending) {
+                    (true, true) => " ▲",
+                    (true, false) => " ▼",
+                    (false, _) => "",
+                };
+                Cell::from(format!("{} {}{}", i + 1, column.title(), arrow))
+            }),
+        ))
+        .style(Style::new().bold().blue());
+
+        let rows = self.rows.iter().map(|index| {
+            let transaction = &self.transactions[*index];
+            let reviewed = if self.annotations.is_reviewed(&transaction.hash) {
+                "✓"
+            } else {
+                ""
+            };
+            let amount = Line::from(format!("{:.2}", transaction.amount)).right_aligned();
+            let amount = if transaction.amount < 0.0 {
+                amount.red()
+            } else {
+                amount.green()
+            };
+
+            Row::new([
+                Cell::from(reviewed).green(),
+                Cell::from(transaction.date.format("%Y-%m-%d").to_string()),
+                Cell::from(amount),
+                Cell::from(transaction.kind.to_string()),
+                Cell::from(transaction.name.clone()),
+                Cell::from(transaction.category.clone().unwrap_or_default()),
+            ])
+        });
+
+        let widths = [
+            Constraint::Length(1),
+            Constraint::Length(10),
+            Constraint::Length(11),
+            Constraint::Length(16),
+            Constraint::Min(16),
+            Constraint::Length(15),
+        ];
+        let title = format!(
+            " Transactions {}/{} ",
+            self.rows.len(),
+            self.transactions.len()
+        );
+        let table = Table::new(rows, widths)
+            .header(header)
+            .block(Block::bordered().title(title))
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+
+        frame.render_stateful_widget(table, area, &mut self.state);
+    }
+
+    fn draw_details(&self, frame: &mut Frame, area: Rect) {
+        let lines = match self.selected() {
+            Some(transaction) => details(transaction),
+            None => Vec::new(),
+        };
+
+        let paragraph = Paragraph::new(lines)
+            .block(Block::bordered().title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(paragraph, area);
+    }
+}
+
+/// A labelled line of the details pane.
+fn field(label: &str, value: impl Into<String>) -> Line<'static> {
+    Line::from(vec![format!("{}: ", label).blue(), Span::raw(value.into())])
+}
+
+/// Everything known about a transaction, with the Gnosis Pay record.
+fn details(transaction: &Transaction) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        field("Hash", transaction.hash.clone()),
+        field(
+            "Date",
+            transaction.date.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        ),
+        field("Amount", format!("{:.2}", transaction.amount)),
+        field("Kind", transaction.kind.to_string()),
+        field("Name", transaction.name.clone()),
+        field("Description", transaction.description.clone()),
+    ];
+
+    if let Some(category) = &transaction.category {
+        lines.push(field("Category", category.clone()));
+    }
+    if let Some(mcc) = &transaction.mcc {
+        let description = mcc::description(mcc).unwrap_or("unknown");
+        lines.push(field("MCC", format!("{} {}", mcc, description)));
+    }
+    if let Some(country) = &transaction.country {
+        lines.push(field("Country", country.clone()));
+    }
+
+    if let Some(details) = &transaction.details {
+        lines.push(Line::default());
+        lines.extend(gnosispay_details(details));
+    }
+
+    lines.push(Line::default());
+    lines.push(field(
+        "Gnosisscan",
+        format!("https://gnosisscan.io/tx/{}", transaction.hash),
+    ));
+    lines
+}
+
+/// Gnosis Pay record of a card payment.
+fn gnosispay_details(details: &GnosisTransaction) -> Vec<Line<'static>> {
+    let mut lines = vec![
+        Line::from("Gnosis Pay").bold(),
+        field(
+            "Merchant",
+            format!(
+                "{}, {}, {}",
+                details.merchant.name.trim(),
+                details.merchant.city.trim(),
+                details.merchant.country.name.trim()
+            ),
+        ),
+        field("Type", details.kind.clone()),
+        field(
+            "Status",
+            format!(
+                "{}{}",
+                details.status.as_deref().unwrap_or("-"),
+                if details.is_pending { ", pending" } else { "" }
+            ),
+        ),
+        field("Created", details.created_at.clone()),
+        field(
+            "Cleared",
+            details
+                .cleared_at
+                .clone()
+                .unwrap_or_else(|| String::from("-")),
+        ),
+    ];
+
+    let paid = to_amount(&details.transaction_amount, &details.transaction_currency);
+    let billed = to_amount(&details.billing_amount, &details.billing_currency);
+    if let (Some(paid), Some(billed)) = (paid, billed) {
+        lines.push(field(
+            "Billed",
+            format!("{:.2} {}", billed, details.billing_currency.symbol),
+        ));
+
+        // Foreign currency purchase, with the rate applied.
+        if details.transaction_currency.code != details.billing_currency.code {
+            lines.push(field(
+                "Paid",
+                format!("{:.2} {}", paid, details.transaction_currency.symbol),
+            ));
+            if paid != 0.0 {
+                lines.push(field("Rate", format!("{:.4}", billed / paid)));
+            }
+        }
+    }
+
+    for transaction in &details.transactions {
+        lines.push(field(
+            "On-chain",
+            format!("{} ({})", transaction.hash, transaction.status),
+        ));
+    }
+    lines
+}
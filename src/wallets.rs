@@ -0,0 +1,41 @@
+use crate::prelude::*;
+use serde::Deserialize;
+use std::fs;
+
+/// A wallet to monitor and who to notify about it.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WalletConfig {
+    pub label: String,
+    pub address: String,
+    /// Pushover user or group key, defaults to `--pushover-user`.
+    pub pushover_user: Option<String>,
+    /// Pushover device, defaults to `--pushover-device`.
+    pub pushover_device: Option<String>,
+    /// Gnosis Pay session token, defaults to `--session-token`.
+    pub session_token: Option<String>,
+    /// Low EURe balance threshold, defaults to `--min-eure-balance`.
+    pub min_eure_balance: Option<f64>,
+    /// Low xDAI balance threshold, defaults to `--min-xdai-balance`.
+    pub min_xdai_balance: Option<f64>,
+}
+
+/// Wallets to monitor, loaded from a TOML file.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Wallets {
+    #[serde(default, rename = "wallet")]
+    pub wallets: Vec<WalletConfig>,
+}
+
+impl Wallets {
+    /// Load wallets from a TOML file.
+    pub fn load(filename: &str) -> Result<Self> {
+        let content = fs::read_to_string(filename)?;
+        let wallets: Wallets = toml::from_str(&content)?;
+
+        if wallets.wallets.is_empty() {
+            return Err(format!("no wallet in `{}`", filename).into());
+        }
+
+        Ok(wallets)
+    }
+}
@@ -0,0 +1,121 @@
+//! Annotations kept in a local JSON file.
+
+use gnosispay::annotations::{Annotation, Annotations, Split};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[test]
+fn reviewed_transactions_are_saved() {
+    let path =
+        std::env::temp_dir().join(format!("gnosispay-annotations-{}.json", std::process::id()));
+    let hash = "0xCCCC";
+
+    let mut annotations = Annotations::load(path.clone()).unwrap();
+    assert!(!annotations.is_reviewed(hash));
+    annotations.entry(hash).reviewed = true;
+    annotations.entry("0xdddd");
+    annotations.save().unwrap();
+
+    // Written next to the file, then renamed over it.
+    let mut temporary = path.clone().into_os_string();
+    temporary.push(".tmp");
+    assert!(!PathBuf::from(temporary).exists());
+
+    // Hashes are case insensitive, empty annotations are not kept.
+    let annotations = Annotations::load(path.clone()).unwrap();
+    assert!(annotations.is_reviewed("0xcccc"));
+    assert!(annotations.get("0xdddd").is_none());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn reload_keeps_annotations_saved_meanwhile() {
+    let path = std::env::temp_dir().join(format!(
+        "gnosispay-annotations-reload-{}.json",
+        std::process::id()
+    ));
+
+    let mut opened = Annotations::load(path.clone()).unwrap();
+    let mut other = Annotations::load(path.clone()).unwrap();
+    other.entry("0xeeee").set_note("Saved by another command");
+    other.save().unwrap();
+
+    opened.reload().unwrap();
+    opened.entry("0xcccc").reviewed = true;
+    opened.save().unwrap();
+
+    let annotations = Annotations::load(path.clone()).unwrap();
+    assert!(annotations.is_reviewed("0xcccc"));
+    assert_eq!(
+        annotations.get("0xeeee").unwrap().note.as_deref(),
+        Some("Saved by another command")
+    );
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn tags_and_notes_are_saved() {
+    let path = std::env::temp_dir().join(format!(
+        "gnosispay-annotations-tags-{}.json",
+        std::process::id()
+    ));
+    let hash = "0xeeee";
+
+    let mut annotations = Annotations::load(path.clone()).unwrap();
+    let annotation = annotations.entry(hash);
+    annotation.tag("business");
+    annotation.tag(" Business ");
+    annotation.tag("trip-2025");
+    annotation.untag("TRIP-2025");
+    annotation.set_note("Lunch with a client");
+    annotation.attach(PathBuf::from("/receipts/lunch.pdf"));
+    annotation.attach(PathBuf::from("/receipts/lunch.pdf"));
+    annotations.save().unwrap();
+
+    let mut annotations = Annotations::load(path.clone()).unwrap();
+    let annotation = annotations.get(hash).unwrap();
+    assert_eq!(annotation.tags, ["business"]);
+    assert_eq!(annotation.note.as_deref(), Some("Lunch with a client"));
+    assert_eq!(annotation.receipts, [PathBuf::from("/receipts/lunch.pdf")]);
+
+    // Without tags, note and receipts, the annotation is dropped.
+    let annotation = annotations.entry(hash);
+    annotation.untag("business");
+    annotation.set_note(" ");
+    annotation.detach(Path::new("/receipts/lunch.pdf"));
+    annotations.save().unwrap();
+    assert!(Annotations::load(path.clone()).unwrap().get(hash).is_none());
+
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn splits_add_up_to_the_amount() {
+    let split = |value: &str| value.parse::<Split>();
+    assert_eq!(
+        split("2.5:Office:printer paper").unwrap(),
+        Split {
+            amount: 2.5,
+            category: String::from("Office"),
+            note: Some(String::from("printer paper")),
+        }
+    );
+    assert!(split("Office").is_err());
+    assert!(split("-2:Office").is_err());
+    assert!(split("2:").is_err());
+
+    let mut annotation = Annotation::default();
+    let parts = vec![
+        split("10.34:Groceries").unwrap(),
+        split("2:Office").unwrap(),
+    ];
+    assert!(annotation.split(parts[..1].to_vec(), -12.34).is_err());
+    assert!(annotation.split(parts.clone(), -12.0).is_err());
+    annotation.split(parts.clone(), -12.34).unwrap();
+    assert_eq!(annotation.splits, parts);
+
+    annotation.split(Vec::new(), -12.34).unwrap();
+    assert!(annotation.is_empty());
+}
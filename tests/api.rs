@@ -0,0 +1,140 @@
+//! Read-only HTTP API served by `serve`.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode, header};
+use gnosispay::api::{Store, router};
+use gnosispay::{GnosisPay, Gnosisscan, Recording, Token, Transactions};
+use http_body_util::BodyExt;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tower::ServiceExt;
+
+const WALLET: &str = "0x14791697260E4c9A71f18484C9f997B308e59325";
+const TOKEN: &str = "secret";
+
+fn fixtures() -> Recording {
+    Recording::Replay(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/app"))
+}
+
+async fn transactions() -> Transactions {
+    let response = Gnosisscan::new(String::new())
+        .with_recording(Some(fixtures()))
+        .retrieve_eure_transactions(String::from(WALLET), Token::Eure)
+        .await
+        .unwrap();
+    let payments = GnosisPay::replay(fixtures())
+        .retrieve_transactions()
+        .await
+        .unwrap();
+
+    let mut transactions = Transactions::new(String::from(WALLET)).unwrap();
+    transactions.import_from_gnosisscan(response).unwrap();
+    transactions
+        .merge_description_from_gnosispay(payments)
+        .unwrap();
+    transactions
+}
+
+async fn loaded() -> Arc<Store> {
+    let store = Arc::new(Store::default());
+    store.update(transactions().await);
+    store
+}
+
+/// Answer to a GET request, with the JSON body, `null` for axum's own rejections.
+async fn get(store: Arc<Store>, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+    let mut request = Request::get(uri);
+    if let Some(token) = token {
+        request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+    }
+
+    let response = router(store, Some(String::from(TOKEN)))
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&body).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn requests_need_the_bearer_token() {
+    let store = loaded().await;
+
+    for token in [None, Some("wrong"), Some("secre"), Some("secrets")] {
+        let (status, body) = get(store.clone(), "/balance", token).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(body["error"], "missing or invalid bearer token");
+    }
+
+    let (status, _) = get(store, "/balance", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn nothing_is_served_before_the_first_load() {
+    let (status, _) = get(Arc::new(Store::default()), "/transactions", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn transactions_are_filtered_by_the_query() {
+    let store = loaded().await;
+    let all = transactions().await;
+
+    let (status, body) = get(store.clone(), "/transactions", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body.as_array().unwrap().len(), all.transactions.len());
+
+    let (status, body) = get(
+        store.clone(),
+        "/transactions?kind=card,mint&direction=out",
+        Some(TOKEN),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let card = body.as_array().unwrap();
+    assert!(!card.is_empty());
+    assert!(card.iter().all(|transaction| transaction["kind"] == "card"));
+
+    // Bad values and unknown parameters are rejected.
+    for uri in [
+        "/transactions?kind=cash",
+        "/transactions?direction=up",
+        "/transactions?from=yesterday",
+        "/transactions?merchant=(",
+        "/transactions?wallet=0x0",
+    ] {
+        let (status, _) = get(store.clone(), uri, Some(TOKEN)).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+#[tokio::test]
+async fn balance_ignores_the_filters_of_other_endpoints() {
+    let store = loaded().await;
+    let all = transactions().await;
+
+    let (_, verify) = get(store.clone(), "/verify?kind=card", Some(TOKEN)).await;
+    let (_, balance) = get(store, "/balance", Some(TOKEN)).await;
+
+    assert!((balance["balance"].as_f64().unwrap() - all.total()).abs() < 1e-9);
+    assert_ne!(
+        verify["count"].as_u64().unwrap() as usize,
+        all.transactions.len()
+    );
+    assert!(verify["total"].as_f64().unwrap() < 0.0);
+}
+
+#[tokio::test]
+async fn reports_are_grouped_by_period() {
+    let store = loaded().await;
+
+    let (status, _) = get(store.clone(), "/reports/monthly", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = get(store.clone(), "/reports/category", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, _) = get(store, "/reports/hourly", Some(TOKEN)).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
@@ -0,0 +1,111 @@
+//! Sign-In With Ethereum used by `login`.
+
+use alloy::primitives::{Address, PrimitiveSignature};
+use alloy::signers::local::PrivateKeySigner;
+use axum::Json;
+use axum::Router;
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use chrono::{DateTime, TimeZone, Utc};
+use gnosispay::{Auth, SiweLogin};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+const NONCE: &str = "Jx8Rk2mQ7vLp";
+const EXPIRES_AT: i64 = 1_767_225_600;
+
+fn login(api_url: &str) -> SiweLogin {
+    SiweLogin::new(
+        String::from(api_url),
+        String::from("app.gnosispay.com"),
+        String::from("https://app.gnosispay.com"),
+    )
+}
+
+/// Fields of an EIP-4361 message, after the statement.
+fn fields(message: &str) -> HashMap<&str, &str> {
+    message
+        .lines()
+        .skip(4)
+        .filter_map(|line| line.split_once(": "))
+        .collect()
+}
+
+#[test]
+fn message_follows_eip_4361() {
+    let address = Address::from_str("0x14791697260e4c9a71f18484c9f997b308e59325").unwrap();
+    let issued_at = Utc.with_ymd_and_hms(2025, 3, 1, 9, 30, 0).unwrap();
+    let message = login("").message(address, NONCE, issued_at);
+
+    assert_eq!(
+        message,
+        "app.gnosispay.com wants you to sign in with your Ethereum account:\n\
+         0x14791697260E4c9A71f18484C9f997B308e59325\n\
+         \n\
+         Sign in with Ethereum to the app.\n\
+         \n\
+         URI: https://app.gnosispay.com\n\
+         Version: 1\n\
+         Chain ID: 100\n\
+         Nonce: Jx8Rk2mQ7vLp\n\
+         Issued At: 2025-03-01T09:30:00.000Z"
+    );
+
+    let fields = fields(&message);
+    assert_eq!(fields["Chain ID"], "100");
+    assert_eq!(fields["Nonce"], NONCE);
+    assert_eq!(
+        DateTime::parse_from_rfc3339(fields["Issued At"]).unwrap(),
+        issued_at
+    );
+}
+
+/// Answer the challenge like the Gnosis Pay API, once the signature and
+/// the nonce of the message are checked.
+async fn challenge(Json(body): Json<Value>) -> (StatusCode, Json<Value>) {
+    let message = body["message"].as_str().unwrap_or_default();
+    let signer = PrimitiveSignature::from_str(body["signature"].as_str().unwrap_or_default())
+        .and_then(|signature| signature.recover_address_from_msg(message));
+    let address = message.lines().nth(1).unwrap_or_default();
+
+    match signer {
+        Ok(signer)
+            if signer.to_checksum(None) == address
+                && fields(message).get("Nonce") == Some(&NONCE) =>
+        {
+            let payload = URL_SAFE_NO_PAD.encode(json!({ "exp": EXPIRES_AT }).to_string());
+            (
+                StatusCode::OK,
+                Json(json!({ "token": format!("eyJhbGciOiJIUzI1NiJ9.{}.signature", payload) })),
+            )
+        }
+        _ => (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({ "error": "invalid signature" })),
+        ),
+    }
+}
+
+#[tokio::test]
+async fn login_exchanges_the_signed_message_for_a_token() {
+    let app = Router::new()
+        .route("/api/v1/auth/nonce", get(|| async { NONCE }))
+        .route("/api/v1/auth/challenge", post(challenge));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let api_url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let signer = PrivateKeySigner::random();
+    let session = login(&api_url).login(&signer).await.unwrap();
+
+    assert_eq!(session.address, signer.address());
+    assert!(matches!(session.auth, Auth::Bearer(_)));
+    assert_eq!(
+        session.auth.expires_at(),
+        DateTime::from_timestamp(EXPIRES_AT, 0)
+    );
+    assert_eq!(Auth::Cookie(String::from("cookie")).expires_at(), None);
+}
@@ -0,0 +1,154 @@
+//! Profiles of the configuration file, read by the command line.
+
+use gnosispay::CsvOptions;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const CONFIG: &str = r#"
+default_profile = "home"
+
+[profile.home]
+wallet_address = "0x1111111111111111111111111111111111111111"
+session_token = "home-session"
+
+[profile.home.export]
+date_format = "%Y-%m-%d"
+
+[profile.work]
+wallet_address = "0x2222222222222222222222222222222222222222"
+"#;
+
+/// Configuration file named after the test, removed by the caller.
+fn config(name: &str, content: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!(
+        "gnosispay-config-{}-{}.toml",
+        name,
+        std::process::id()
+    ));
+    fs::write(&path, content).unwrap();
+    path
+}
+
+/// Run the command line with `args`, away from the user's settings.
+fn run(args: &[&str], profile: Option<&str>) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_gnosispay-cli"));
+    command
+        .args(args)
+        .env(
+            "XDG_CONFIG_HOME",
+            std::env::temp_dir().join("gnosispay-none"),
+        )
+        .env_remove("GNOSISPAY_CONFIG")
+        .env_remove("GNOSISPAY_PROFILE");
+    if let Some(profile) = profile {
+        command.env("GNOSISPAY_PROFILE", profile);
+    }
+    command.output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn profiles_are_selected_by_flag_environment_or_default() {
+    let path = config("profiles", CONFIG);
+    let path = path.to_str().unwrap();
+    let show = |extra: &[&str], profile| {
+        let mut args = vec!["config", "show", "--config", path];
+        args.extend_from_slice(extra);
+        run(&args, profile)
+    };
+
+    let output = show(&[], None);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("[+] Profile `home`"));
+    assert!(stdout(&output).contains("0x1111111111111111111111111111111111111111"));
+    assert!(stdout(&output).contains("date_format = \"%Y-%m-%d\""));
+    // Secrets are redacted.
+    assert!(!stdout(&output).contains("home-session"));
+
+    let output = show(&["--profile", "work"], None);
+    assert!(stdout(&output).contains("[+] Profile `work`"));
+    let output = show(&[], Some("work"));
+    assert!(stdout(&output).contains("0x2222222222222222222222222222222222222222"));
+
+    // The flag wins over the environment.
+    let output = show(&["--profile", "home"], Some("work"));
+    assert!(stdout(&output).contains("[+] Profile `home`"));
+
+    let output = show(&["--profile", "office"], None);
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("unknown profile `office`"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn missing_files_are_only_an_error_when_asked_for() {
+    let output = run(&["config", "show"], None);
+    assert!(output.status.success(), "{}", stderr(&output));
+    assert!(stdout(&output).contains("[+] No profile selected"));
+
+    let output = run(
+        &["config", "show", "--config", "/nonexistent/config.toml"],
+        None,
+    );
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("cannot read config"));
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    for (name, content, field) in [
+        (
+            "typo",
+            "[profile.home]\nwallet = \"0x1111111111111111111111111111111111111111\"\n",
+            "wallet",
+        ),
+        (
+            "section",
+            "[profile.home.export]\nseparator = \";\"\n",
+            "separator",
+        ),
+        ("top", "default = \"home\"\n", "default"),
+    ] {
+        let path = config(name, content);
+        let output = run(
+            &["config", "show", "--config", path.to_str().unwrap()],
+            None,
+        );
+        fs::remove_file(&path).unwrap();
+
+        assert!(!output.status.success(), "{}", name);
+        assert!(
+            stderr(&output).contains(&format!("unknown field `{}`", field)),
+            "{}",
+            stderr(&output)
+        );
+    }
+}
+
+#[test]
+fn date_formats_are_checked_on_load() {
+    let path = config(
+        "date",
+        "[profile.home.export]\ndate_format = \"%Y-%m-%Q\"\n",
+    );
+    let output = run(
+        &["config", "show", "--config", path.to_str().unwrap()],
+        None,
+    );
+    fs::remove_file(&path).unwrap();
+
+    assert!(!output.status.success());
+    assert!(stderr(&output).contains("profile `home`: invalid date format `%Y-%m-%Q`"));
+
+    assert!(CsvOptions::check_date_format("%d/%m/%Y %H:%M").is_ok());
+    assert!(CsvOptions::check_date_format("%").is_err());
+}
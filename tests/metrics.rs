@@ -0,0 +1,89 @@
+//! Prometheus metrics and health served by `monitor --metrics-listen`.
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use gnosispay::metrics::{Metrics, router};
+use http_body_util::BodyExt;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+use tower::ServiceExt;
+
+/// Answer to a GET request, with the text body.
+async fn get(metrics: Arc<Metrics>, uri: &str) -> (StatusCode, String) {
+    let response = router(metrics, 60)
+        .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    (status, String::from_utf8(body.to_vec()).unwrap())
+}
+
+#[tokio::test]
+async fn metrics_are_in_the_prometheus_text_format() {
+    let metrics = Arc::new(Metrics::default());
+    metrics.connected.store(true, Ordering::Relaxed);
+    metrics.events_seen.store(7, Ordering::Relaxed);
+    metrics.notification(true);
+    metrics.notification(false);
+    metrics.block_seen(100);
+    metrics.chain_head.store(103, Ordering::Relaxed);
+    metrics.spent("Alice \"card\"", 12.5);
+    metrics.spent("Alice \"card\"", 2.25);
+    metrics.balance("Bob", "EURe", 42.5);
+
+    let (status, body) = get(metrics, "/metrics").await;
+    assert_eq!(status, StatusCode::OK);
+
+    for line in [
+        "# HELP gnosispay_monitor_connected Whether the websocket subscription is up.",
+        "# TYPE gnosispay_monitor_connected gauge",
+        "gnosispay_monitor_connected 1",
+        "# TYPE gnosispay_monitor_events_total counter",
+        "gnosispay_monitor_events_total 7",
+        "gnosispay_monitor_notifications_sent_total 1",
+        "gnosispay_monitor_notifications_failed_total 1",
+        "gnosispay_monitor_last_block 100",
+        "gnosispay_monitor_lag_blocks 3",
+        "# TYPE gnosispay_wallet_payments_total counter",
+        "gnosispay_wallet_payments_total{wallet=\"Alice \\\"card\\\"\"} 2",
+        "gnosispay_wallet_spent_total{wallet=\"Alice \\\"card\\\"\"} 14.75",
+        "# TYPE gnosispay_wallet_balance gauge",
+        "gnosispay_wallet_balance{wallet=\"Bob\",asset=\"EURe\"} 42.5",
+    ] {
+        assert!(
+            body.lines().any(|sample| sample == line),
+            "missing `{}`",
+            line
+        );
+    }
+
+    // Every sample follows its HELP and TYPE lines.
+    for sample in body.lines().filter(|line| !line.starts_with('#')) {
+        let name = sample.split(['{', ' ']).next().unwrap();
+        assert!(
+            body.contains(&format!("# TYPE {} ", name)),
+            "no type for `{}`",
+            name
+        );
+    }
+}
+
+#[tokio::test]
+async fn health_needs_a_recent_block() {
+    let metrics = Arc::new(Metrics::default());
+
+    let (status, _) = get(metrics.clone(), "/healthz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+    metrics.connected.store(true, Ordering::Relaxed);
+    metrics.block_seen(1);
+    let (status, body) = get(metrics.clone(), "/healthz").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body, "ok\n");
+
+    // A block seen long ago means the subscription is stuck.
+    metrics.last_block_seen_at.fetch_sub(120, Ordering::Relaxed);
+    let (status, _) = get(metrics, "/healthz").await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+}
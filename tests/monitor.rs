@@ -0,0 +1,119 @@
+//! Transfers and balances followed by `monitor` for several wallets.
+
+use alloy::primitives::{Address, B256};
+use alloy::rpc::types::FilteredParams;
+use alloy::sol_types::SolEvent;
+use chrono::{Duration, Local, TimeZone};
+use gnosispay::Token;
+use gnosispay::monitor::{BalanceThresholds, IERC20, LowBalances, Wallet, transfer_filters};
+use gnosispay::notify::PushOverNotify;
+use gnosispay::wallets::Wallets;
+use std::fs;
+
+fn wallet(label: &str, byte: u8) -> Wallet {
+    Wallet {
+        label: String::from(label),
+        address: Address::repeat_byte(byte),
+        pushover: PushOverNotify::new(String::new(), String::new()),
+        gnosispay: None,
+        thresholds: BalanceThresholds::default(),
+    }
+}
+
+/// Whether a transfer of `token` from `from` to `to` matches the filter.
+fn matches(filter: &alloy::rpc::types::Filter, token: Token, from: Address, to: Address) -> bool {
+    let params = FilteredParams::new(Some(filter.clone()));
+    params.filter_address(&token.address())
+        && params.filter_topics(&[
+            IERC20::Transfer::SIGNATURE_HASH,
+            from.into_word(),
+            to.into_word(),
+        ])
+}
+
+#[test]
+fn transfers_of_any_wallet_are_followed() {
+    let wallets = [wallet("Alice", 1), wallet("Bob", 2)];
+    let [outgoing, incoming] = transfer_filters(Token::Eure, &wallets);
+    let (alice, bob) = (wallets[0].address, wallets[1].address);
+    let stranger = Address::repeat_byte(3);
+
+    // Sent by either wallet.
+    assert!(matches(&outgoing, Token::Eure, alice, stranger));
+    assert!(matches(&outgoing, Token::Eure, bob, Address::ZERO));
+    assert!(!matches(&outgoing, Token::Eure, stranger, alice));
+
+    // Received by either wallet.
+    assert!(matches(&incoming, Token::Eure, Address::ZERO, alice));
+    assert!(matches(&incoming, Token::Eure, stranger, bob));
+    assert!(!matches(&incoming, Token::Eure, alice, stranger));
+
+    // Transfers of others and of other tokens are left out.
+    for filter in [&outgoing, &incoming] {
+        assert!(!matches(filter, Token::Eure, stranger, Address::ZERO));
+        assert!(!matches(filter, Token::EureV1, alice, bob));
+        assert!(!FilteredParams::new(Some(filter.clone())).filter_topics(&[
+            B256::ZERO,
+            alice.into_word(),
+            bob.into_word()
+        ]));
+    }
+}
+
+#[test]
+fn wallets_file_lists_each_wallet() {
+    let filename =
+        std::env::temp_dir().join(format!("gnosispay-wallets-{}.toml", std::process::id()));
+    fs::write(
+        &filename,
+        r#"
+[[wallet]]
+label = "Alice"
+address = "0x0101010101010101010101010101010101010101"
+min_eure_balance = 20.0
+
+[[wallet]]
+label = "Bob"
+address = "0x0202020202020202020202020202020202020202"
+pushover_user = "bob"
+"#,
+    )
+    .unwrap();
+    let wallets = Wallets::load(filename.to_str().unwrap()).unwrap().wallets;
+    fs::write(&filename, "").unwrap();
+    let empty = Wallets::load(filename.to_str().unwrap());
+    fs::remove_file(&filename).unwrap();
+
+    assert_eq!(wallets.len(), 2);
+    assert_eq!(wallets[0].label, "Alice");
+    assert_eq!(wallets[0].min_eure_balance, Some(20.0));
+    assert_eq!(wallets[1].pushover_user.as_deref(), Some("bob"));
+    assert!(empty.unwrap_err().to_string().starts_with("no wallet in"));
+}
+
+#[test]
+fn low_balances_alert_once_until_topped_up() {
+    let mut low_balances = LowBalances::default();
+    let (alice, bob) = (Address::repeat_byte(1), Address::repeat_byte(2));
+    let now = Local.with_ymd_and_hms(2025, 3, 1, 9, 0, 0).unwrap();
+
+    // Above the threshold, nothing to tell.
+    assert!(!low_balances.alert(alice, "EURe", 25.0, 20.0, now));
+
+    // Crossing it is told once, not at every check.
+    assert!(low_balances.alert(alice, "EURe", 19.99, 20.0, now));
+    let later = now + Duration::minutes(5);
+    assert!(!low_balances.alert(alice, "EURe", 15.0, 20.0, later));
+
+    // Other assets and wallets are told on their own.
+    assert!(low_balances.alert(alice, "xDAI", 0.01, 0.1, later));
+    assert!(low_balances.alert(bob, "EURe", 5.0, 20.0, later));
+
+    // Still low a day later, a reminder.
+    assert!(low_balances.alert(alice, "EURe", 15.0, 20.0, now + Duration::hours(24)));
+
+    // Topped up, the next drop is told at once.
+    let later = now + Duration::hours(25);
+    assert!(!low_balances.alert(alice, "EURe", 20.0, 20.0, later));
+    assert!(low_balances.alert(alice, "EURe", 10.0, 20.0, later));
+}
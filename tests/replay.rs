@@ -0,0 +1,735 @@
+//! Transactions built from responses recorded with `--record`.
+
+use chrono::{DateTime, NaiveDate};
+use gnosispay::address_book::AddressBook;
+use gnosispay::annotations::Annotations;
+use gnosispay::budget::{Budget, Period, Progress};
+use gnosispay::filter::{Direction, Filter};
+use gnosispay::mcc::Overrides;
+use gnosispay::report::{GroupBy, Report};
+use gnosispay::summary::{Summary, YearStart};
+use gnosispay::transactions::Kind;
+use gnosispay::{CsvOptions, GnosisPay, Gnosisscan, Recording, Token, Transaction, Transactions};
+use std::fs;
+use std::path::PathBuf;
+
+const WALLET: &str = "0x14791697260E4c9A71f18484C9f997B308e59325";
+const CARD_PAYMENT: &str = "0xcccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccccc";
+
+fn fixtures(name: &str) -> Recording {
+    Recording::Replay(
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+    )
+}
+
+fn gnosisscan() -> Gnosisscan {
+    Gnosisscan::new(String::new()).with_recording(Some(fixtures("app")))
+}
+
+async fn imported() -> Transactions {
+    let response = gnosisscan()
+        .retrieve_eure_transactions(String::from(WALLET), Token::Eure)
+        .await
+        .unwrap();
+
+    let mut transactions = Transactions::new(String::from(WALLET)).unwrap();
+    transactions.import_from_gnosisscan(response).unwrap();
+    transactions
+}
+
+async fn with_safe_executions() -> Transactions {
+    let response = gnosisscan()
+        .retrieve_safe_logs(String::from(WALLET))
+        .await
+        .unwrap();
+
+    let mut transactions = merged().await;
+    transactions.import_safe_executions(response).unwrap();
+    transactions
+}
+
+async fn merged() -> Transactions {
+    let payments = GnosisPay::replay(fixtures("app"))
+        .retrieve_transactions()
+        .await
+        .unwrap();
+
+    let mut transactions = imported().await;
+    transactions
+        .merge_description_from_gnosispay(payments)
+        .unwrap();
+    transactions
+}
+
+#[tokio::test]
+async fn import_from_gnosisscan_classifies_transfers() {
+    let transactions = imported().await;
+    assert_eq!(transactions.transactions.len(), 4);
+
+    let name = |hash: char| {
+        let transaction = &transactions.transactions[&format!("0x{}", hash.to_string().repeat(64))];
+        (transaction.name.as_str(), transaction.amount)
+    };
+
+    assert_eq!(name('a'), ("Minted on chain", 100.0));
+    assert_eq!(name('b'), ("Added funds on chain", 50.0));
+    assert_eq!(name('c'), ("From Gnosiscard", -12.34));
+    assert_eq!(name('d'), ("Sent funds on chain", -20.0));
+}
+
+#[tokio::test]
+async fn import_from_gnosisscan_names_known_counterparties() {
+    let response = gnosisscan()
+        .retrieve_eure_transactions(String::from(WALLET), Token::Eure)
+        .await
+        .unwrap();
+
+    let mut address_book = AddressBook::default();
+    address_book.insert(
+        "0x1111111111111111111111111111111111111111"
+            .parse()
+            .unwrap(),
+        String::from("Kraken"),
+    );
+    let mut transactions = Transactions::new(String::from(WALLET))
+        .unwrap()
+        .with_address_book(address_book);
+    transactions.import_from_gnosisscan(response).unwrap();
+
+    let deposit = &transactions.transactions[&format!("0x{}", "b".repeat(64))];
+    assert_eq!(deposit.name, "Added funds from Kraken");
+    assert_eq!(deposit.description, format!("From 0x{}", "1".repeat(40)));
+
+    let sent = &transactions.transactions[&format!("0x{}", "d".repeat(64))];
+    assert_eq!(sent.name, "Sent funds on chain");
+
+    // Well-known contracts are named without configuration.
+    let address_book = AddressBook::default();
+    assert_eq!(
+        address_book.label(
+            "0x9008D19f58AAbD9eD0D60971565AA8510560ab41"
+                .parse()
+                .unwrap()
+        ),
+        Some("CoW Protocol")
+    );
+    assert_eq!(
+        address_book.label(Token::Eure.address()),
+        Some("Monerium EURe")
+    );
+}
+
+#[tokio::test]
+async fn merge_description_from_gnosispay_names_card_payments() {
+    let transactions = merged().await;
+    assert_eq!(transactions.transactions.len(), 4);
+
+    let payment = &transactions.transactions[CARD_PAYMENT];
+    assert_eq!(payment.name, "Cafe de Flore");
+    assert_eq!(payment.description, "Paris - France");
+    assert_eq!(payment.category.as_deref(), Some("Restaurants"));
+}
+
+#[tokio::test]
+async fn categorize_prefers_merchant_overrides() {
+    let mut transactions = merged().await;
+    let mut overrides = Overrides::default();
+    overrides
+        .mcc
+        .insert(String::from("5812"), String::from("Eating out"));
+    transactions.categorize(&overrides);
+    assert_eq!(
+        transactions.transactions[CARD_PAYMENT].category.as_deref(),
+        Some("Eating out")
+    );
+
+    overrides
+        .merchant
+        .insert(String::from("cafe de flore"), String::from("Treats"));
+    transactions.categorize(&overrides);
+    assert_eq!(
+        transactions.transactions[CARD_PAYMENT].category.as_deref(),
+        Some("Treats")
+    );
+}
+
+#[tokio::test]
+async fn gnosispay_api_pages_are_replayed() {
+    let payments = GnosisPay::replay(fixtures("api"))
+        .retrieve_transactions()
+        .await
+        .unwrap();
+
+    assert_eq!(payments.len(), 2);
+    assert_eq!(payments[0].transactions[0].hash, CARD_PAYMENT);
+}
+
+#[tokio::test]
+async fn import_safe_executions_finds_owner_withdrawals() {
+    let transactions = with_safe_executions().await;
+    assert_eq!(transactions.transactions.len(), 5);
+
+    let withdrawal = &transactions.transactions[&format!("0x{}", "d".repeat(64))];
+    assert_eq!(withdrawal.kind, Kind::Withdrawal);
+    assert_eq!(withdrawal.name, "Withdrawn by owner");
+    assert_eq!(withdrawal.amount, -20.0);
+
+    let failure = &transactions.transactions[&format!("0x{}", "f".repeat(64))];
+    assert_eq!(failure.kind, Kind::SafeFailure);
+    assert_eq!(failure.amount, 0.0);
+
+    // Card payments and deposits are left alone.
+    assert_eq!(transactions.transactions[CARD_PAYMENT].kind, Kind::Card);
+    let deposit = &transactions.transactions[&format!("0x{}", "b".repeat(64))];
+    assert_eq!(deposit.kind, Kind::Deposit);
+}
+
+#[tokio::test]
+async fn import_safe_executions_finds_module_withdrawals() {
+    let gnosisscan = Gnosisscan::new(String::new()).with_recording(Some(fixtures("module")));
+    let response = gnosisscan
+        .retrieve_eure_transactions(String::from(WALLET), Token::Eure)
+        .await
+        .unwrap();
+    let logs = gnosisscan
+        .retrieve_safe_logs(String::from(WALLET))
+        .await
+        .unwrap();
+
+    let mut transactions = Transactions::new(String::from(WALLET)).unwrap();
+    transactions.import_from_gnosisscan(response).unwrap();
+    transactions.import_safe_executions(logs).unwrap();
+    assert_eq!(transactions.transactions.len(), 2);
+
+    let withdrawal = &transactions.transactions[&format!("0x{}", "e".repeat(64))];
+    assert_eq!(withdrawal.kind, Kind::Withdrawal);
+    assert_eq!(withdrawal.amount, -30.0);
+    assert_eq!(
+        withdrawal.description,
+        format!("Executed by module 0x{}", "3".repeat(40))
+    );
+
+    let failure = &transactions.transactions[&format!("0x{}", "9".repeat(64))];
+    assert_eq!(failure.kind, Kind::SafeFailure);
+    assert_eq!(failure.amount, 0.0);
+}
+
+#[tokio::test]
+async fn verify_sums_all_transactions() {
+    let transactions = merged().await;
+    assert!((transactions.total() - 117.66).abs() < 1e-9);
+}
+
+#[tokio::test]
+async fn to_csv_sorts_by_date() {
+    let mut transactions = merged().await;
+    let mut annotations = Annotations::default();
+    let annotation = annotations.entry(CARD_PAYMENT);
+    annotation.tag("business");
+    annotation.tag("trip");
+    annotation.set_note("Lunch, with \"Bob\"");
+    transactions.annotate(&annotations);
+
+    let filename =
+        std::env::temp_dir().join(format!("gnosispay-replay-{}.csv", std::process::id()));
+
+    let options = CsvOptions {
+        delimiter: ',',
+        date_format: String::from("%Y-%m-%d"),
+    };
+    transactions
+        .to_csv(filename.display().to_string(), &options)
+        .unwrap();
+    let csv = fs::read_to_string(&filename).unwrap();
+    fs::remove_file(&filename).unwrap();
+
+    let a = "a".repeat(64);
+    let b = "b".repeat(64);
+    let c = "c".repeat(64);
+    let d = "d".repeat(64);
+    let one = "1".repeat(40);
+    let two = "2".repeat(40);
+    let expected = format!(
+        "ID,Date,Amount,Name,Description,Category,Tags,Note,Receipts\n\
+         0x{a},2025-01-01,100.00,Minted on chain,,,,,\n\
+         0x{b},2025-01-04,50.00,Added funds on chain,From 0x{one},,,,\n\
+         0x{c},2025-01-05,-12.34,Cafe de Flore,Paris - France,Restaurants,\"business, trip\",\"Lunch, with \"\"Bob\"\"\",\n\
+         0x{d},2025-01-06,-20.00,Sent funds on chain,To 0x{two},,,,\n"
+    );
+    assert_eq!(csv, expected);
+}
+
+#[tokio::test]
+async fn to_csv_rejects_a_bad_date_format() {
+    let transactions = merged().await;
+    let options = CsvOptions {
+        delimiter: ',',
+        date_format: String::from("%Y-%Q"),
+    };
+    let filename =
+        std::env::temp_dir().join(format!("gnosispay-replay-date-{}.csv", std::process::id()));
+
+    assert!(
+        transactions
+            .to_csv(filename.display().to_string(), &options)
+            .is_err()
+    );
+    assert!(!filename.exists());
+    assert!(CsvOptions::check_date_format("%d/%m/%Y %H:%M").is_ok());
+}
+
+/// The card payment split between groceries and office supplies.
+async fn split() -> Transactions {
+    let mut transactions = with_safe_executions().await;
+    let mut annotations = Annotations::default();
+    annotations
+        .entry(CARD_PAYMENT)
+        .split(
+            vec![
+                "10.34:Groceries".parse().unwrap(),
+                "2:Office:paper".parse().unwrap(),
+            ],
+            -12.34,
+        )
+        .unwrap();
+    transactions.annotate(&annotations);
+    transactions
+}
+
+/// Content of a file written by `export`, removed afterwards.
+fn exported(extension: &str, export: impl FnOnce(String) -> gnosispay::Result<()>) -> String {
+    let filename = std::env::temp_dir().join(format!(
+        "gnosispay-replay-split-{}.{}",
+        std::process::id(),
+        extension
+    ));
+    export(filename.display().to_string()).unwrap();
+    let content = fs::read_to_string(&filename).unwrap();
+    fs::remove_file(&filename).unwrap();
+    content
+}
+
+#[tokio::test]
+async fn to_csv_writes_a_line_per_split() {
+    let transactions = split().await;
+    let csv = exported("csv", |filename| {
+        transactions.to_csv(filename, &CsvOptions::default())
+    });
+
+    let lines: Vec<_> = csv.lines().filter(|line| line.contains("0xccc")).collect();
+    assert_eq!(
+        lines,
+        [
+            format!("{CARD_PAYMENT};05-01-2025;-10.34;Cafe de Flore;Paris - France;Groceries;;;"),
+            format!("{CARD_PAYMENT};05-01-2025;-2.00;Cafe de Flore;Paris - France;Office;;paper;"),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn to_qif_writes_split_lines() {
+    let transactions = split().await;
+    let qif = exported("qif", |filename| transactions.to_qif(filename));
+
+    assert!(qif.starts_with("!Type:Bank\n"));
+    let card = qif
+        .split("^\n")
+        .find(|record| record.contains(CARD_PAYMENT))
+        .unwrap();
+    assert_eq!(
+        card,
+        format!(
+            "D01/05/2025\nT-12.34\nN{CARD_PAYMENT}\nPCafe de Flore\nMParis - France\n\
+             SGroceries\n$-10.34\nSOffice\nEpaper\n$-2.00\n"
+        )
+    );
+
+    // Safe transactions without transfer are left out.
+    assert_eq!(qif.matches("^\n").count(), 4);
+}
+
+#[tokio::test]
+async fn to_ofx_writes_a_statement_line_per_split() {
+    let transactions = split().await;
+    let ofx = exported("ofx", |filename| transactions.to_ofx(filename));
+
+    assert!(ofx.contains(&format!("<ACCTID>{}</ACCTID>", WALLET)));
+    assert!(ofx.contains("<BALAMT>117.66</BALAMT>"));
+    assert!(ofx.contains(&format!(
+        "<TRNAMT>-10.34</TRNAMT>\n<FITID>{CARD_PAYMENT}-1</FITID>\n<NAME>Cafe de Flore</NAME>\n<MEMO>Groceries</MEMO>"
+    )));
+    assert!(ofx.contains(&format!(
+        "<TRNAMT>-2.00</TRNAMT>\n<FITID>{CARD_PAYMENT}-2</FITID>\n<NAME>Cafe de Flore</NAME>\n<MEMO>Office: paper</MEMO>"
+    )));
+    assert_eq!(ofx.matches("<STMTTRN>").count(), 5);
+}
+
+#[tokio::test]
+async fn to_ledger_writes_a_posting_per_split() {
+    let mut transactions = split().await;
+    let deposit = format!("0x{}", "b".repeat(64));
+    transactions
+        .transactions
+        .get_mut(&deposit)
+        .unwrap()
+        .category = Some(String::from("Refunds"));
+    let ledger = exported("ledger", |filename| transactions.to_ledger(filename));
+
+    let card = ledger
+        .split("\n\n")
+        .find(|entry| entry.contains(CARD_PAYMENT))
+        .unwrap();
+    let lines: Vec<_> = card.lines().map(str::trim_end).collect();
+    assert_eq!(
+        lines,
+        [
+            format!("2025/01/05 Cafe de Flore  ; {CARD_PAYMENT}"),
+            String::from("    ; Paris - France"),
+            String::from("    Expenses:Groceries                             10.34 EUR"),
+            String::from("    Expenses:Office                                 2.00 EUR  ; paper"),
+            String::from("    Assets:Gnosis Pay"),
+        ]
+    );
+
+    // Funds received are income, even when categorized.
+    let deposit = ledger
+        .split("\n\n")
+        .find(|entry| entry.contains(&deposit))
+        .unwrap();
+    assert!(deposit.contains("    Income:Refunds                                -50.00 EUR"));
+}
+
+#[tokio::test]
+async fn splits_count_in_their_categories() {
+    let transactions = split().await;
+
+    let report = Report::new(&transactions, GroupBy::Category);
+    let row = |key: &str| {
+        let row = report.rows.iter().find(|row| row.key == key).unwrap();
+        (row.count, row.expense)
+    };
+    assert_eq!(row("Groceries"), (1, 10.34));
+    assert_eq!(row("Office"), (1, 2.0));
+    assert!(report.rows.iter().all(|row| row.key != "Restaurants"));
+    assert_eq!(report.total.count, 4);
+    assert!((report.total.expense - 32.34).abs() < 1e-9);
+
+    let budget = |category: &str| Budget {
+        category: Some(String::from(category)),
+        merchant: None,
+        amount: 15.0,
+        period: Period::Monthly,
+    };
+    let now = DateTime::parse_from_rfc3339("2025-01-06T10:00:00Z").unwrap();
+    let payments = transactions.transactions.values();
+    assert_eq!(budget("groceries").spent(payments.clone(), &now), 10.34);
+    assert_eq!(budget("Office").spent(payments.clone(), &now), 2.0);
+    assert_eq!(budget("Restaurants").spent(payments, &now), 0.0);
+
+    let summary = Summary::new(&transactions, 2025, YearStart::default()).unwrap();
+    let category = |name: &str| {
+        let total = &summary.card_spend_by_category[name];
+        (total.count, total.amount)
+    };
+    assert_eq!(category("Groceries"), (1, 10.34));
+    assert_eq!(category("Office"), (1, 2.0));
+    assert_eq!(summary.card_spend.amount, 12.34);
+}
+
+#[tokio::test]
+async fn safe_logs_are_read_page_by_page() {
+    let dir = std::env::temp_dir().join(format!("gnosispay-logs-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let log = |index: usize| {
+        serde_json::json!({
+            "address": WALLET.to_lowercase(),
+            "topics": [],
+            "data": "0x",
+            "blockNumber": "0x1",
+            "timeStamp": "0x677c4f40",
+            "transactionHash": format!("0x{:064x}", index),
+        })
+    };
+    let page = |logs: Vec<serde_json::Value>| {
+        serde_json::json!({ "status": "1", "message": "OK", "result": logs }).to_string()
+    };
+    let name = format!("gnosisscan_{}_logs", WALLET.to_lowercase());
+    fs::write(
+        dir.join(format!("{}.json", name)),
+        page((0..1000).map(log).collect()),
+    )
+    .unwrap();
+    fs::write(dir.join(format!("{}_2.json", name)), page(vec![log(1000)])).unwrap();
+
+    let response = Gnosisscan::new(String::new())
+        .with_recording(Some(Recording::Replay(dir.clone())))
+        .retrieve_safe_logs(String::from(WALLET))
+        .await;
+    fs::remove_dir_all(&dir).unwrap();
+
+    // The second page is short, a third one is never asked for.
+    let logs = response.unwrap().result.unwrap();
+    assert_eq!(logs.len(), 1001);
+    assert_eq!(logs[1000].transaction_hash, format!("0x{:064x}", 1000));
+}
+
+#[tokio::test]
+async fn missing_recording_is_an_error() {
+    let result = Gnosisscan::new(String::new())
+        .with_recording(Some(fixtures("app")))
+        .retrieve_eure_transactions(String::from(WALLET), Token::EureV1)
+        .await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn report_aggregates_by_week() {
+    let transactions = with_safe_executions().await;
+    let report = Report::new(&transactions, GroupBy::Week);
+
+    let rows: Vec<_> = report
+        .rows
+        .iter()
+        .map(|row| (row.key.as_str(), row.count, row.income, row.expense))
+        .collect();
+    assert_eq!(
+        rows,
+        [("2025-W01", 3, 150.0, 12.34), ("2025-W02", 1, 0.0, 20.0)]
+    );
+    assert!((report.rows[1].expense_delta.unwrap() - 7.66).abs() < 1e-9);
+    assert!((report.total.net - 117.66).abs() < 1e-9);
+}
+
+#[test]
+fn report_compares_with_the_previous_month_even_without_spending() {
+    let mut transactions = Transactions::new(String::from(WALLET)).unwrap();
+    for (hash, date, amount) in [("0x01", "2025-01-10", -30.0), ("0x02", "2025-03-10", -50.0)] {
+        transactions.transactions.insert(
+            String::from(hash),
+            Transaction {
+                hash: String::from(hash),
+                date: DateTime::parse_from_rfc3339(&format!("{}T12:00:00Z", date))
+                    .unwrap()
+                    .to_utc(),
+                amount,
+                name: String::from("Shop"),
+                description: String::new(),
+                kind: Kind::Card,
+                mcc: None,
+                country: None,
+                category: None,
+                details: None,
+                annotation: Default::default(),
+            },
+        );
+    }
+
+    let report = Report::new(&transactions, GroupBy::Month);
+    let rows: Vec<_> = report
+        .rows
+        .iter()
+        .map(|row| (row.key.as_str(), row.count, row.expense, row.expense_delta))
+        .collect();
+    assert_eq!(
+        rows,
+        [
+            ("2025-01", 1, 30.0, None),
+            ("2025-02", 0, 0.0, Some(-30.0)),
+            ("2025-03", 1, 50.0, Some(50.0)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn report_aggregates_by_country() {
+    let transactions = with_safe_executions().await;
+    let report = Report::new(&transactions, GroupBy::Country);
+
+    // Sorted by expense, on-chain transfers have no country.
+    let keys: Vec<_> = report.rows.iter().map(|row| row.key.as_str()).collect();
+    assert_eq!(keys, ["-", "France"]);
+    assert!(report.rows.iter().all(|row| row.expense_delta.is_none()));
+}
+
+#[tokio::test]
+async fn budgets_count_card_payments_of_the_period() {
+    let transactions = with_safe_executions().await;
+    let budget = |category: Option<&str>, merchant: Option<&str>, period| Budget {
+        category: category.map(String::from),
+        merchant: merchant.map(String::from),
+        amount: 15.0,
+        period,
+    };
+    let budgets = [
+        budget(Some("restaurants"), None, Period::Monthly),
+        budget(None, Some("CAFE DE FLORE"), Period::Weekly),
+        budget(Some("Groceries"), None, Period::Monthly),
+    ];
+
+    // The card payment is on Sunday 2025-01-05, on-chain transfers are ignored.
+    let now = DateTime::parse_from_rfc3339("2025-01-06T10:00:00Z").unwrap();
+    let progress = Progress::of(&budgets, transactions.transactions.values(), &now);
+    let spent: Vec<_> = progress.iter().map(|progress| progress.spent).collect();
+    assert_eq!(spent, [12.34, 0.0, 0.0]);
+    assert!(progress[0].ratio() > 0.8);
+
+    assert_eq!(
+        Period::Weekly.start(NaiveDate::from_ymd_opt(2025, 1, 5).unwrap()),
+        NaiveDate::from_ymd_opt(2024, 12, 30).unwrap()
+    );
+
+    // Crossing both thresholds at once warns about the highest.
+    assert_eq!(budgets[0].crossed(10.0, 12.34), Some(0.8));
+    assert_eq!(budgets[0].crossed(10.0, 16.0), Some(1.0));
+    assert_eq!(budgets[0].crossed(12.5, 13.0), None);
+}
+
+#[tokio::test]
+async fn gnosispay_payments_convert_to_transactions() {
+    let payments = GnosisPay::replay(fixtures("app"))
+        .retrieve_transactions()
+        .await
+        .unwrap();
+
+    let transaction = Transaction::from_gnosispay(&payments[0]).unwrap().unwrap();
+    assert_eq!(transaction.hash, CARD_PAYMENT);
+    assert_eq!(transaction.amount, -12.34);
+    assert_eq!(transaction.name, "Cafe de Flore");
+    assert_eq!(transaction.category.as_deref(), Some("Restaurants"));
+}
+
+#[tokio::test]
+async fn retain_applies_all_conditions() {
+    let count = |filter: Filter| async move {
+        let mut transactions = with_safe_executions().await;
+        transactions.retain(&filter);
+        let mut hashes: Vec<_> = transactions
+            .transactions
+            .keys()
+            .map(|hash| hash.chars().last().unwrap())
+            .collect();
+        hashes.sort();
+        hashes
+    };
+
+    let filter = Filter {
+        direction: Some(Direction::In),
+        ..Filter::default()
+    };
+    assert_eq!(count(filter).await, ['a', 'b']);
+
+    let filter = Filter {
+        kinds: vec![Kind::Card, Kind::Withdrawal],
+        min_amount: Some(15.0),
+        ..Filter::default()
+    };
+    assert_eq!(count(filter).await, ['d']);
+
+    let filter = Filter {
+        merchant: Some(
+            regex::RegexBuilder::new("de fl")
+                .case_insensitive(true)
+                .build()
+                .unwrap(),
+        ),
+        countries: vec![String::from("france")],
+        currencies: vec![String::from("978")],
+        ..Filter::default()
+    };
+    assert_eq!(count(filter).await, ['c']);
+
+    let filter = Filter {
+        from: NaiveDate::from_ymd_opt(2025, 1, 2),
+        to: NaiveDate::from_ymd_opt(2025, 1, 4),
+        ..Filter::default()
+    };
+    assert_eq!(count(filter).await, ['b']);
+}
+
+#[test]
+fn safe_executions_are_only_needed_when_they_can_match() {
+    assert!(Filter::default().needs_safe_executions());
+
+    // Sent funds may turn out to be withdrawals.
+    for kinds in [vec![Kind::Sent], vec![Kind::Card, Kind::SafeFailure]] {
+        let filter = Filter {
+            kinds,
+            ..Filter::default()
+        };
+        assert!(filter.needs_safe_executions());
+    }
+
+    let filter = Filter {
+        kinds: vec![Kind::Card, Kind::Deposit],
+        ..Filter::default()
+    };
+    assert!(!filter.needs_safe_executions());
+
+    let filter = Filter {
+        direction: Some(Direction::In),
+        ..Filter::default()
+    };
+    assert!(!filter.needs_safe_executions());
+}
+
+#[tokio::test]
+async fn summary_totals_the_fiscal_year() {
+    let mut transactions = with_safe_executions().await;
+
+    // Paid in US dollars, billed in euros.
+    let details = transactions
+        .transactions
+        .get_mut(CARD_PAYMENT)
+        .unwrap()
+        .details
+        .as_mut()
+        .unwrap();
+    details.transaction_amount = String::from("1350");
+    details.transaction_currency.code = String::from("840");
+    details.transaction_currency.symbol = String::from("$");
+    details.transaction_currency.name = String::from("US Dollar");
+
+    let summary = Summary::new(&transactions, 2025, YearStart::default()).unwrap();
+    assert_eq!(
+        (summary.from.to_string(), summary.to.to_string()),
+        (String::from("2025-01-01"), String::from("2025-12-31"))
+    );
+    assert_eq!(
+        (summary.card_spend.count, summary.card_spend.amount),
+        (1, 12.34)
+    );
+    assert_eq!((summary.sends.count, summary.sends.amount), (1, 20.0));
+    assert_eq!((summary.top_ups.count, summary.top_ups.amount), (2, 150.0));
+    assert_eq!(summary.top_ups_by_source["Minted on chain"].amount, 100.0);
+    assert_eq!(summary.refunds.count, 0);
+
+    let foreign = &summary.foreign_currencies[0];
+    assert_eq!(
+        (foreign.name.as_str(), foreign.amount, foreign.eur),
+        ("US Dollar", 13.5, 12.34)
+    );
+
+    let summary = Summary::new(&transactions, 2024, YearStart::default()).unwrap();
+    assert_eq!(summary.card_spend.count + summary.top_ups.count, 0);
+
+    // A fiscal year is named after the year it starts in.
+    let start: YearStart = "04-06".parse().unwrap();
+    let (from, to) = start.period(2024).unwrap();
+    assert_eq!(
+        (from.to_string(), to.to_string()),
+        (String::from("2024-04-06"), String::from("2025-04-05"))
+    );
+    assert_eq!(
+        start.year_of(NaiveDate::from_ymd_opt(2025, 1, 5).unwrap()),
+        2024
+    );
+    assert!("02-29".parse::<YearStart>().is_err());
+
+    // Years chrono cannot represent are an error, not a panic.
+    assert!(start.period(300_000).is_err());
+    assert!(Summary::new(&transactions, i32::MAX, YearStart::default()).is_err());
+}
@@ -0,0 +1,121 @@
+//! Monitor alerting rules.
+
+use chrono::{DateTime, Local, TimeZone};
+use gnosispay::gnosispay::GnosisTransaction;
+use gnosispay::notify::{Message, Priority};
+use gnosispay::rules::{Channel, Payment, Rules};
+use std::fs;
+use std::path::PathBuf;
+
+const RULES: &str = r#"
+[quiet_hours]
+start = "22:00"
+end = "07:00"
+
+[[rule]]
+name = "big"
+min_amount = 100.0
+priority = "high"
+ignore_quiet_hours = true
+
+[[rule]]
+name = "cafe"
+merchant = "flore"
+sound = "cashregister"
+
+[[rule]]
+name = "spree"
+daily_total_above = 200.0
+channel = "console"
+"#;
+
+/// The card payment of the recorded Gnosis Pay transactions.
+fn cafe() -> GnosisTransaction {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures/app/gnosispay_app_transactions.json");
+    let transactions: Vec<GnosisTransaction> =
+        serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap();
+    transactions.into_iter().next().unwrap()
+}
+
+fn at(hour: u32) -> DateTime<Local> {
+    Local.with_ymd_and_hms(2025, 1, 5, hour, 30, 0).unwrap()
+}
+
+fn payment(amount: f64, hour: u32, daily_total: f64) -> Payment {
+    Payment {
+        amount: -amount,
+        time: at(hour),
+        daily_total,
+        details: None,
+    }
+}
+
+#[test]
+fn first_matching_rule_wins() {
+    let rules: Rules = toml::from_str(RULES).unwrap();
+
+    let (name, action) = rules.evaluate(&payment(150.0, 12, 150.0));
+    assert_eq!(name.as_deref(), Some("big"));
+    assert_eq!(action.priority, Some(Priority::High));
+
+    // Merchant conditions need the Gnosis Pay details.
+    let (name, _) = rules.evaluate(&payment(12.34, 12, 12.34));
+    assert_eq!(name, None);
+    let with_details = Payment {
+        details: Some(cafe()),
+        ..payment(12.34, 12, 12.34)
+    };
+    let (name, action) = rules.evaluate(&with_details);
+    assert_eq!(name.as_deref(), Some("cafe"));
+    assert_eq!(action.sound.as_deref(), Some("cashregister"));
+
+    let (name, action) = rules.evaluate(&payment(20.0, 12, 250.0));
+    assert_eq!(name.as_deref(), Some("spree"));
+    assert_eq!(action.channel, Channel::Console);
+}
+
+#[test]
+fn quiet_hours_apply_without_matching_rule() {
+    let rules: Rules = toml::from_str(RULES).unwrap();
+
+    let (name, action) = rules.evaluate(&payment(5.0, 23, 5.0));
+    assert_eq!(name, None);
+    assert_eq!(action.priority, Some(Priority::Lowest));
+    assert_eq!(action.channel, Channel::Pushover);
+
+    // Around midnight, and not for rules ignoring them.
+    let (_, action) = rules.evaluate(&payment(5.0, 6, 5.0));
+    assert_eq!(action.priority, Some(Priority::Lowest));
+    let (_, action) = rules.evaluate(&payment(5.0, 7, 5.0));
+    assert_eq!(action.priority, None);
+    let (_, action) = rules.evaluate(&payment(150.0, 23, 150.0));
+    assert_eq!(action.priority, Some(Priority::High));
+}
+
+#[test]
+fn apply_keeps_the_message_priority_unless_set() {
+    let rules: Rules = toml::from_str(RULES).unwrap();
+    let message = || Message {
+        sound: Some(String::from("bike")),
+        ..Message::new(String::from("title"), String::from("text")).priority(Priority::Low)
+    };
+
+    let (_, action) = rules.evaluate(&payment(20.0, 12, 250.0));
+    let applied = action.apply(message());
+    assert_eq!(applied.priority, Priority::Low);
+    assert_eq!(applied.sound.as_deref(), Some("bike"));
+
+    let (_, action) = rules.evaluate(&payment(150.0, 12, 150.0));
+    assert_eq!(action.apply(message()).priority, Priority::High);
+
+    let with_details = Payment {
+        details: Some(cafe()),
+        ..payment(12.34, 12, 12.34)
+    };
+    let (_, action) = rules.evaluate(&with_details);
+    assert_eq!(
+        action.apply(message()).sound.as_deref(),
+        Some("cashregister")
+    );
+}
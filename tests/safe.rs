@@ -0,0 +1,141 @@
+//! Delay module transactions and spending allowance.
+
+use alloy::primitives::{Address, B256, Bytes, U256, address};
+use alloy::sol_types::SolCall;
+use chrono::{DateTime, Duration};
+use gnosispay::Token;
+use gnosispay::address_book::AddressBook;
+use gnosispay::monitor::IERC20;
+use gnosispay::safe::{Allowance, DelayQueue, IDelay, QueueStatus, QueuedTransaction};
+
+const RECIPIENT: Address = address!("0x2222222222222222222222222222222222222222");
+
+fn queued(to: Address, data: Bytes, expiration: u64) -> QueuedTransaction {
+    let event = IDelay::TransactionAdded {
+        queueNonce: U256::from(3),
+        txHash: B256::repeat_byte(1),
+        to,
+        value: U256::ZERO,
+        data,
+        operation: 0,
+    };
+    QueuedTransaction::new(
+        event,
+        DateTime::from_timestamp(0, 0).unwrap(),
+        180,
+        expiration,
+    )
+}
+
+#[test]
+fn eure_transfer_is_decoded() {
+    let data = IERC20::transferCall {
+        to: RECIPIENT,
+        value: U256::from(20_500_000_000_000_000_000u128),
+    }
+    .abi_encode();
+    let transaction = queued(Token::Eure.address(), data.into(), 1800);
+
+    assert_eq!(transaction.nonce, 3);
+    assert_eq!(
+        transaction.transfer(),
+        Some((RECIPIENT, U256::from(20_500_000_000_000_000_000u128)))
+    );
+    let mut address_book = AddressBook::default();
+    assert_eq!(
+        transaction.summary(&address_book),
+        format!("20.50 EURe to {}", RECIPIENT)
+    );
+
+    address_book.insert(RECIPIENT, String::from("Savings"));
+    assert_eq!(
+        transaction.summary(&address_book),
+        format!("20.50 EURe to Savings ({})", RECIPIENT)
+    );
+}
+
+#[test]
+fn queue_names_recipients_after_the_address_book() {
+    let data = IERC20::transferCall {
+        to: RECIPIENT,
+        value: U256::from(20_500_000_000_000_000_000u128),
+    }
+    .abi_encode();
+    let queue = DelayQueue {
+        module: Address::repeat_byte(9),
+        cooldown: 180,
+        expiration: 1800,
+        tx_nonce: 2,
+        queue_nonce: 4,
+        transactions: vec![queued(Token::Eure.address(), data.into(), 1800)],
+        expired: vec![2],
+        address_book: AddressBook::default(),
+    };
+    assert!(!queue.to_string().contains("Savings"));
+    assert!(queue.to_string().contains("Expired nonces: 2"));
+
+    let mut address_book = AddressBook::default();
+    address_book.insert(RECIPIENT, String::from("Savings"));
+    let queue = queue.with_address_book(address_book);
+    assert!(
+        queue
+            .to_string()
+            .contains(&format!("Savings ({})", RECIPIENT))
+    );
+}
+
+#[test]
+fn status_follows_cooldown_and_expiration() {
+    let transaction = queued(RECIPIENT, Bytes::new(), 1800);
+    let created_at = transaction.created_at;
+
+    assert_eq!(
+        transaction.status(created_at + Duration::seconds(60)),
+        QueueStatus::CoolingDown
+    );
+    assert_eq!(
+        transaction.status(created_at + Duration::seconds(180)),
+        QueueStatus::Executable
+    );
+    assert_eq!(
+        transaction.status(created_at + Duration::seconds(1980)),
+        QueueStatus::Expired
+    );
+
+    // Without expiration a transaction stays executable.
+    let transaction = queued(RECIPIENT, Bytes::new(), 0);
+    assert_eq!(
+        transaction.status(created_at + Duration::days(365)),
+        QueueStatus::Executable
+    );
+}
+
+#[test]
+fn allowance_refills_every_period() {
+    let eure = |amount: u64| U256::from(amount) * U256::from(10u64).pow(U256::from(18));
+    let allowance = Allowance {
+        refill: eure(100),
+        max_refill: eure(250),
+        period: 86400,
+        balance: eure(30),
+        timestamp: 1_000_000,
+    };
+
+    // Within the period the balance is what is left.
+    assert_eq!(allowance.accrued(1_000_000 + 3600), (eure(30), 1_000_000));
+
+    // Two periods later, refilled twice.
+    assert_eq!(
+        allowance.accrued(1_000_000 + 2 * 86400 + 60),
+        (eure(230), 1_000_000 + 2 * 86400)
+    );
+
+    // Refills stop at the maximum.
+    assert_eq!(allowance.accrued(1_000_000 + 10 * 86400).0, eure(250));
+
+    let now = DateTime::from_timestamp(1_000_000 + 3600, 0).unwrap();
+    assert_eq!(
+        allowance.next_refill(now),
+        DateTime::from_timestamp(1_000_000 + 86400, 0)
+    );
+}
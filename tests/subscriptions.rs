@@ -0,0 +1,131 @@
+//! Detection of recurring card payments.
+
+use chrono::{DateTime, Utc};
+use gnosispay::Transaction;
+use gnosispay::subscriptions::{Cadence, Subscription};
+use gnosispay::transactions::Kind;
+
+fn payment(name: &str, date: &str, amount: f64) -> Transaction {
+    let date = DateTime::parse_from_rfc3339(&format!("{}T09:00:00Z", date))
+        .unwrap()
+        .with_timezone(&Utc);
+
+    Transaction {
+        hash: format!("{}-{}", name, date),
+        date,
+        amount: -amount,
+        name: String::from(name),
+        description: String::new(),
+        kind: Kind::Card,
+        mcc: None,
+        country: None,
+        category: None,
+        details: None,
+        annotation: Default::default(),
+    }
+}
+
+#[test]
+fn monthly_charges_are_detected_with_price_increase() {
+    let transactions = [
+        payment("Streaming", "2025-01-15", 13.49),
+        payment("Streaming", "2025-02-15", 13.49),
+        payment("Streaming", "2025-03-17", 13.49),
+        payment("Streaming", "2025-04-15", 17.99),
+        // Same merchant every week, but not the same amount.
+        payment("Bakery", "2025-01-06", 4.20),
+        payment("Bakery", "2025-01-13", 23.10),
+        payment("Bakery", "2025-01-20", 3.80),
+        // Too few charges.
+        payment("Gym", "2025-01-01", 30.0),
+        payment("Gym", "2025-02-01", 30.0),
+    ];
+
+    let subscriptions = Subscription::detect(&transactions);
+    assert_eq!(subscriptions.len(), 1);
+
+    let subscription = &subscriptions[0];
+    assert_eq!(subscription.merchant, "Streaming");
+    assert_eq!(subscription.cadence, Cadence::Monthly);
+    assert_eq!(subscription.charges, 4);
+    assert_eq!(subscription.amount, 17.99);
+    assert_eq!(subscription.previous_amount, Some(13.49));
+    assert!(subscription.price_increased());
+    assert_eq!(
+        subscription
+            .next_charge
+            .unwrap()
+            .format("%Y-%m-%d")
+            .to_string(),
+        "2025-05-15"
+    );
+
+    assert!(subscription.differs(19.99));
+    assert!(!subscription.differs(17.99));
+}
+
+#[test]
+fn weekly_charges_tolerate_a_late_one() {
+    let transactions = [
+        payment("Box", "2025-01-01", 25.0),
+        payment("Box", "2025-01-08", 25.0),
+        payment("Box", "2025-01-15", 25.0),
+        payment("Box", "2025-01-25", 25.0),
+        payment("Box", "2025-02-01", 25.0),
+    ];
+
+    let subscriptions = Subscription::detect(&transactions);
+    assert_eq!(subscriptions[0].cadence, Cadence::Weekly);
+    assert_eq!(subscriptions[0].previous_amount, None);
+}
+
+#[test]
+fn foreign_currency_charges_keep_their_price() {
+    // A USD subscription, charged in EUR at the exchange rate of the day.
+    let transactions = [
+        payment("Cloud", "2025-01-03", 9.61),
+        payment("Cloud", "2025-02-03", 9.58),
+        payment("Cloud", "2025-03-03", 9.66),
+        payment("Cloud", "2025-04-03", 9.63),
+    ];
+
+    let subscriptions = Subscription::detect(&transactions);
+    let subscription = &subscriptions[0];
+    assert_eq!(subscription.previous_amount, None);
+    assert_eq!(subscription.usual_amount, 9.63);
+    assert!(!subscription.differs(9.71));
+    assert!(subscription.differs(11.99));
+}
+
+#[test]
+fn merchants_match_whatever_their_case() {
+    let transactions = [
+        payment("ÉPICERIE Café", "2025-01-10", 20.0),
+        payment("Épicerie café", "2025-02-10", 20.0),
+        payment("épicerie CAFÉ", "2025-03-10", 20.0),
+    ];
+
+    let subscriptions = Subscription::detect(&transactions);
+    assert_eq!(subscriptions.len(), 1);
+
+    let next = payment(" épicerie café ", "2025-04-10", 20.0);
+    assert!(Subscription::find(&subscriptions, &next).is_some());
+}
+
+#[test]
+fn subscriptions_without_recent_charge_lapse() {
+    let transactions = [
+        payment("Streaming", "2025-01-15", 13.49),
+        payment("Streaming", "2025-02-15", 13.49),
+        payment("Streaming", "2025-03-15", 13.49),
+    ];
+    let subscription = &Subscription::detect(&transactions)[0];
+
+    let day = |date: &str| {
+        DateTime::parse_from_rfc3339(&format!("{}T09:00:00Z", date))
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+    assert!(!subscription.is_lapsed(day("2025-04-18")));
+    assert!(subscription.is_lapsed(day("2025-06-01")));
+}